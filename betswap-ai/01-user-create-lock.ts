@@ -207,9 +207,11 @@ async function createLockOnNear(amountHuman: string) {
   for (const receipt of receipts) {
     if (receipt.outcome && receipt.outcome.logs) {
       for (const log of receipt.outcome.logs) {
-        const match = log.match(/Escrow created: (\d+)/);
-        if (match) {
-          escrowId = Number(match[1]);
+        // NEP-297 events: EVENT_JSON:{"standard":"betswap","event":"escrow_created",...}
+        if (!log.startsWith("EVENT_JSON:")) continue;
+        const event = JSON.parse(log.slice("EVENT_JSON:".length));
+        if (event.standard === "betswap" && event.event === "escrow_created") {
+          escrowId = Number(event.data.escrow_id);
           break;
         }
       }
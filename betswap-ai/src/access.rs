@@ -0,0 +1,268 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, AccountId, IntoStorageKey};
+
+use crate::events::Event;
+
+/// Roles the owner can delegate
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Manages configuration and grants every role but `Admin`
+    Admin,
+    MarketCreator,
+    Oracle,
+    SolverManager,
+    Pauser,
+}
+
+/// Owner and role assignments of a contract
+///
+/// The owner holds every role. Admins hold every role and can grant and revoke
+/// all roles except `Admin`, which only the owner manages.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct AccessControl {
+    pub owner_id: AccountId,
+    roles: LookupMap<AccountId, Vec<Role>>,
+}
+
+impl AccessControl {
+    pub fn new<S: IntoStorageKey>(owner_id: AccountId, prefix: S) -> Self {
+        Self {
+            owner_id,
+            roles: LookupMap::new(prefix),
+        }
+    }
+
+    /// Roles granted explicitly to `account_id`
+    pub fn roles_of(&self, account_id: &AccountId) -> Vec<Role> {
+        self.roles.get(account_id).unwrap_or_default()
+    }
+
+    pub fn has_role(&self, account_id: &AccountId, role: Role) -> bool {
+        if *account_id == self.owner_id {
+            return true;
+        }
+        let roles = self.roles_of(account_id);
+        roles.contains(&role) || roles.contains(&Role::Admin)
+    }
+
+    /// Panic unless the predecessor holds `role`
+    pub fn assert_role(&self, role: Role) {
+        assert!(self.has_role(&env::predecessor_account_id(), role), "Missing role {:?}", role);
+    }
+
+    pub fn assert_owner(&self) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner");
+    }
+
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        self.assert_can_manage(role);
+        let mut roles = self.roles_of(&account_id);
+        if !roles.contains(&role) {
+            roles.push(role);
+            self.roles.insert(&account_id, &roles);
+        }
+        Event::RoleGranted { account_id, role, by: env::predecessor_account_id() }.emit();
+    }
+
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        self.assert_can_manage(role);
+        let mut roles = self.roles_of(&account_id);
+        roles.retain(|granted| *granted != role);
+        if roles.is_empty() {
+            self.roles.remove(&account_id);
+        } else {
+            self.roles.insert(&account_id, &roles);
+        }
+        Event::RoleRevoked { account_id, role, by: env::predecessor_account_id() }.emit();
+    }
+
+    pub fn transfer_ownership(&mut self, new_owner_id: AccountId) {
+        self.assert_owner();
+        let old_owner_id = std::mem::replace(&mut self.owner_id, new_owner_id.clone());
+        Event::OwnershipTransferred { old_owner_id, new_owner_id }.emit();
+    }
+
+    fn assert_can_manage(&self, role: Role) {
+        let caller = env::predecessor_account_id();
+        let allowed = match role {
+            Role::Admin => caller == self.owner_id,
+            _ => self.has_role(&caller, Role::Admin),
+        };
+        assert!(allowed, "Cannot manage role {:?}", role);
+    }
+}
+
+/// Parts of a contract that can be paused on their own
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Betting,
+    Quoting,
+    Execution,
+    Claims,
+}
+
+/// Circuit breaker with a global switch and one per subsystem
+///
+/// Refunds and withdrawals are never paused, so users can always exit.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+pub struct PauseState {
+    pub global: bool,
+    pub subsystems: Vec<Subsystem>,
+}
+
+impl PauseState {
+    pub fn is_paused(&self, subsystem: Subsystem) -> bool {
+        self.global || self.subsystems.contains(&subsystem)
+    }
+
+    /// Panic if `subsystem` is paused
+    pub fn assert_not_paused(&self, subsystem: Subsystem) {
+        assert!(!self.is_paused(subsystem), "{:?} paused", subsystem);
+    }
+
+    /// Pause `subsystem`, or everything when `None`
+    pub fn pause(&mut self, subsystem: Option<Subsystem>) {
+        match subsystem {
+            None => self.global = true,
+            Some(subsystem) if !self.subsystems.contains(&subsystem) => self.subsystems.push(subsystem),
+            Some(_) => {}
+        }
+        Event::Paused { subsystem, by: env::predecessor_account_id() }.emit();
+    }
+
+    /// Lift the pause of `subsystem`, or the global pause when `None`
+    pub fn unpause(&mut self, subsystem: Option<Subsystem>) {
+        match subsystem {
+            None => self.global = false,
+            Some(subsystem) => self.subsystems.retain(|paused| *paused != subsystem),
+        }
+        Event::Unpaused { subsystem, by: env::predecessor_account_id() }.emit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    fn account(name: &str) -> AccountId {
+        name.parse().unwrap()
+    }
+
+    fn set_caller(caller: &str) {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(account(caller));
+        testing_env!(context.build());
+    }
+
+    fn setup() -> AccessControl {
+        set_caller("owner.testnet");
+        AccessControl::new(account("owner.testnet"), b"r".as_slice())
+    }
+
+    #[test]
+    fn owner_holds_every_role() {
+        let access = setup();
+        assert!(access.has_role(&account("owner.testnet"), Role::Pauser));
+        assert!(!access.has_role(&account("alice.testnet"), Role::Pauser));
+    }
+
+    #[test]
+    fn grant_and_revoke_roles() {
+        let mut access = setup();
+        access.grant_role(account("alice.testnet"), Role::Oracle);
+        access.grant_role(account("alice.testnet"), Role::Oracle);
+        assert_eq!(access.roles_of(&account("alice.testnet")), vec![Role::Oracle]);
+        assert!(access.has_role(&account("alice.testnet"), Role::Oracle));
+        assert!(!access.has_role(&account("alice.testnet"), Role::MarketCreator));
+        assert_eq!(
+            near_sdk::test_utils::get_logs()[0],
+            Event::RoleGranted {
+                account_id: account("alice.testnet"),
+                role: Role::Oracle,
+                by: account("owner.testnet"),
+            }
+            .to_log()
+        );
+
+        access.revoke_role(account("alice.testnet"), Role::Oracle);
+        assert!(access.roles_of(&account("alice.testnet")).is_empty());
+    }
+
+    #[test]
+    fn admin_manages_other_roles() {
+        let mut access = setup();
+        access.grant_role(account("admin.testnet"), Role::Admin);
+        assert!(access.has_role(&account("admin.testnet"), Role::SolverManager));
+
+        set_caller("admin.testnet");
+        access.grant_role(account("bob.testnet"), Role::MarketCreator);
+        assert!(access.has_role(&account("bob.testnet"), Role::MarketCreator));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot manage role Admin")]
+    fn only_owner_grants_admin() {
+        let mut access = setup();
+        access.grant_role(account("admin.testnet"), Role::Admin);
+        set_caller("admin.testnet");
+        access.grant_role(account("bob.testnet"), Role::Admin);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot manage role Oracle")]
+    fn role_holders_cannot_grant() {
+        let mut access = setup();
+        access.grant_role(account("alice.testnet"), Role::Oracle);
+        set_caller("alice.testnet");
+        access.grant_role(account("bob.testnet"), Role::Oracle);
+    }
+
+    #[test]
+    fn ownership_transfer() {
+        let mut access = setup();
+        access.transfer_ownership(account("alice.testnet"));
+        assert_eq!(access.owner_id, account("alice.testnet"));
+        assert!(!access.has_role(&account("owner.testnet"), Role::Admin));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner")]
+    fn only_owner_transfers_ownership() {
+        let access = &mut setup();
+        set_caller("alice.testnet");
+        access.transfer_ownership(account("alice.testnet"));
+    }
+
+    #[test]
+    fn pause_switches() {
+        setup();
+        let mut pause = PauseState::default();
+        pause.pause(Some(Subsystem::Betting));
+        assert!(pause.is_paused(Subsystem::Betting));
+        assert!(!pause.is_paused(Subsystem::Claims));
+
+        pause.pause(None);
+        assert!(pause.is_paused(Subsystem::Claims));
+        pause.unpause(None);
+        assert!(!pause.is_paused(Subsystem::Claims));
+        assert!(pause.is_paused(Subsystem::Betting));
+
+        pause.unpause(Some(Subsystem::Betting));
+        assert!(!pause.is_paused(Subsystem::Betting));
+    }
+
+    #[test]
+    #[should_panic(expected = "Quoting paused")]
+    fn global_pause_covers_every_subsystem() {
+        setup();
+        let mut pause = PauseState::default();
+        pause.pause(None);
+        pause.assert_not_paused(Subsystem::Quoting);
+    }
+}
@@ -1,17 +1,180 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LookupMap, UnorderedMap};
+use near_sdk::collections::{LookupMap, UnorderedMap, UnorderedSet};
 use near_sdk::json_types::{U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault, Promise};
+use near_sdk::{env, near_bindgen, require, AccountId, Gas, NearToken, PanicOnDefault, Promise, PromiseOrValue, PromiseResult};
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+use crate::access::{AccessControl, PauseState, Role, Subsystem};
+use crate::escrow::ext_ft;
+use crate::events::Event;
+
+use crate::signature;
+use crate::storage::{StorageBalance, StorageBalanceBounds, StorageLedger};
+use crate::{mul_div, paginate, ChainSignature};
+
+// Gas for the NEP-141 `ft_transfer` of winnings and bonds
+const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+
+// Gas reserved for the winnings transfer callback
+const GAS_FOR_RESOLVE_CLAIM: Gas = Gas::from_tgas(10);
+
+// Default time to challenge a resolution (1 day in nanoseconds)
+const DEFAULT_DISPUTE_PERIOD: u64 = 86_400_000_000_000;
+
+// Default challenge bond, in basis points of the event's total stake
+const DEFAULT_CHALLENGE_BOND_BPS: u32 = 100;
+
+// Time a cross-chain bet's swap intent stays open (1 hour in nanoseconds)
+const BET_INTENT_TTL: u64 = 3_600_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize)]
 pub struct BetEvent {
     pub description: String,
+    pub token: Option<AccountId>, // NEP-141 stake token, `None` for native NEAR
     pub end_time: u64,
     pub resolved: bool,
-    pub outcome: bool,
+    pub outcomes: Vec<String>,
+    pub outcome: u32, // Index into `outcomes` once resolved
     pub total_bets: u128,
-    pub user_bets: LookupMap<AccountId, u128>,
+    pub pools: Vec<u128>, // Total stake per outcome
+    pub user_bets: LookupMap<AccountId, UserStake>,
+    pub quorum: OracleQuorum,
+    pub disputed: bool,
+    pub dispute_end: u64, // Claims open once the resolution can no longer be challenged
+    pub challenge: Option<Challenge>,
+    pub min_confidence: u64, // Predictions below this are advisory only
+    pub resolution: String, // "pending", "auto", "needs_review", "manual" or "void"
+}
+
+// A bonded challenge of a provisional resolution
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+pub struct Challenge {
+    pub challenger: AccountId,
+    pub outcome: u32, // Outcome the challenger claims is correct
+    pub bond: U128,
+    pub created_at: U64,
+}
+
+// Oracles allowed to resolve an event and how many of them must agree
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+pub struct OracleQuorum {
+    pub oracles: Vec<String>, // Public keys, empty for any registered oracle
+    pub threshold: u32,
+    pub dispute_margin: u32, // Dissenting reports tolerated before the event is disputed, below `threshold`
+}
+
+impl Default for OracleQuorum {
+    fn default() -> Self {
+        Self {
+            oracles: Vec::new(),
+            threshold: 1,
+            dispute_margin: 0,
+        }
+    }
+}
+
+// A verified oracle report on an event
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+pub struct OracleSubmission {
+    pub public_key: String,
+    pub outcome: u32,
+    pub confidence: u64,
+    pub timestamp: U64,
+    pub report: String, // Raw signed report
+}
+
+// JSON view of a `BetEvent`, without the per-user stakes
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BetEventView {
+    pub description: String,
+    pub token: Option<AccountId>,
+    pub end_time: U64,
+    pub resolved: bool,
+    pub outcomes: Vec<String>,
+    pub outcome: u32,
+    pub total_bets: U128,
+    pub pools: Vec<U128>,
+    pub quorum: OracleQuorum,
+    pub disputed: bool,
+    pub dispute_end: U64,
+    pub challenge: Option<Challenge>,
+    pub min_confidence: u64,
+    pub resolution: String,
+    pub status: String,
+}
+
+impl From<BetEvent> for BetEventView {
+    fn from(event: BetEvent) -> Self {
+        Self {
+            status: event.status().to_string(),
+            description: event.description,
+            token: event.token,
+            end_time: U64(event.end_time),
+            resolved: event.resolved,
+            outcomes: event.outcomes,
+            outcome: event.outcome,
+            total_bets: U128(event.total_bets),
+            pools: event.pools.into_iter().map(U128).collect(),
+            quorum: event.quorum,
+            disputed: event.disputed,
+            dispute_end: U64(event.dispute_end),
+            challenge: event.challenge,
+            min_confidence: event.min_confidence,
+            resolution: event.resolution,
+        }
+    }
+}
+
+impl BetEvent {
+    // Pool backing `outcome`
+    pub fn pool(&self, outcome: u32) -> u128 {
+        self.pools.get(outcome as usize).copied().unwrap_or(0)
+    }
+
+    // "open", "closed" (awaiting oracles), "disputed", "needs_review", "resolved" or "void"
+    pub fn status(&self) -> &'static str {
+        if self.resolution == "void" {
+            "void"
+        } else if self.resolved {
+            "resolved"
+        } else if self.resolution == "needs_review" {
+            "needs_review"
+        } else if self.disputed {
+            "disputed"
+        } else if env::block_timestamp() < self.end_time {
+            "open"
+        } else {
+            "closed"
+        }
+    }
+}
+
+// Labels of binary markets, so `false`/`true` bets map to outcomes 0 and 1
+pub const BINARY_OUTCOMES: [&str; 2] = ["No", "Yes"];
+
+// Oracle report outcome voting to void the event
+pub const VOID_OUTCOME: u32 = u32::MAX;
+
+// A user's stake on each outcome of an event
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+pub struct UserStake {
+    pub stakes: Vec<u128>,
+    pub claimed: bool,
+}
+
+impl UserStake {
+    pub fn on(&self, outcome: u32) -> u128 {
+        self.stakes.get(outcome as usize).copied().unwrap_or(0)
+    }
+}
+
+/// Bet parameters sent as the `msg` of `ft_transfer_call`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BetMsg {
+    pub event_id: String,
+    pub outcome: u32,
+    #[serde(default)]
+    pub challenge: bool, // Post the transfer as a challenge bond for `outcome` instead
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
@@ -19,7 +182,7 @@ pub struct CrossChainBet {
     pub user: AccountId,
     pub event_id: String,
     pub amount: u128,
-    pub outcome: bool,
+    pub outcome: u32,
     pub is_cross_chain: bool,
     pub eth_address: String,
     pub completed: bool,
@@ -28,10 +191,23 @@ pub struct CrossChainBet {
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
 pub struct AIOutcomePrediction {
     pub event_id: String,
-    pub predicted_outcome: bool,
+    pub predicted_outcome: u32,
     pub confidence: u64,
-    pub oracle_data: String,
+    pub oracle_data: String, // Raw oracle report, kept for audit
     pub timestamp: u64,
+    pub advisory: bool, // Below the event's minimum confidence, not used to resolve
+}
+
+// Outcome report signed by a registered oracle over
+// `signature::oracle_report_message(event_id, outcome, confidence, timestamp)`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OracleReport {
+    pub event_id: String,
+    pub outcome: u32,
+    pub confidence: u64, // 0-100
+    pub timestamp: U64,
+    pub public_key: String,
+    pub signature: String,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
@@ -39,16 +215,19 @@ pub struct BetSwapIntent {
     pub user: AccountId,
     pub event_id: String,
     pub amount: u128,
-    pub outcome: bool,
+    pub outcome: u32,
     pub target_chain: String, // "ETH" or "NEAR"
     pub deadline: u64,
 }
 
+// Quote request for a cross-chain bet swap, stored at the requester's expense
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
-pub struct ChainSignature {
-    pub signature: String,
-    pub intent_id: String,
-    pub timestamp: u64,
+pub struct BetQuoteRequest {
+    pub requester: AccountId,
+    pub from_token: String,
+    pub to_token: String,
+    pub from_amount: U128,
+    pub deadline: U64,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
@@ -57,9 +236,9 @@ pub struct MetaOrder {
     pub user: AccountId,
     pub event_id: String,
     pub amount: u128,
-    pub outcome: bool,
+    pub outcome: u32,
     pub intent_id: String,
-    pub signature: String,
+    pub signature: ChainSignature,
     pub is_executed: bool,
 }
 
@@ -77,20 +256,31 @@ pub struct TEEConfig {
     pub enclave_id: String,
     pub attestation: String,
     pub is_verified: bool,
+    pub public_key: String, // Key the solver signs meta-orders with
 }
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct BetSwapAI {
+    pub access: AccessControl,
+    pub pause: PauseState,
+    pub storage: StorageLedger,
+    pub arbitrator: Option<AccountId>,
+    pub dispute_period: u64,
+    pub challenge_bond_bps: u32,
+    pub accepted_tokens: UnorderedSet<AccountId>,
+    pub oracles: UnorderedSet<String>, // Oracle public keys
     pub bet_events: UnorderedMap<String, BetEvent>,
     pub cross_chain_bets: UnorderedMap<String, CrossChainBet>,
+    pub unpaid_challenges: LookupMap<String, (AccountId, u128)>, // Challenger payouts whose transfer failed, per event
     pub user_rewards: LookupMap<AccountId, u128>,
     pub ai_predictions: UnorderedMap<String, AIOutcomePrediction>,
+    pub oracle_submissions: UnorderedMap<String, Vec<OracleSubmission>>,
     pub bet_intents: UnorderedMap<String, BetSwapIntent>,
     pub meta_orders: UnorderedMap<String, MetaOrder>,
     pub solvers: UnorderedMap<AccountId, SolverConfig>,
     pub solver_reputation: LookupMap<AccountId, u64>,
-    pub quote_requests: UnorderedMap<String, String>,
+    pub quote_requests: UnorderedMap<String, BetQuoteRequest>,
     pub order_counter: u64,
     pub quote_counter: u64,
 }
@@ -98,66 +288,243 @@ pub struct BetSwapAI {
 #[near_bindgen]
 impl BetSwapAI {
     #[init]
-    pub fn new() -> Self {
+    pub fn new(owner_id: AccountId) -> Self {
         Self {
-            bet_events: UnorderedMap::new(b"bet_events"),
-            cross_chain_bets: UnorderedMap::new(b"cross_chain_bets"),
-            user_rewards: LookupMap::new(b"user_rewards"),
-            ai_predictions: UnorderedMap::new(b"ai_predictions"),
-            bet_intents: UnorderedMap::new(b"bet_intents"),
-            meta_orders: UnorderedMap::new(b"meta_orders"),
-            solvers: UnorderedMap::new(b"solvers"),
-            solver_reputation: LookupMap::new(b"solver_reputation"),
-            quote_requests: UnorderedMap::new(b"quote_requests"),
+            access: AccessControl::new(owner_id, b"roles".as_slice()),
+            pause: PauseState::default(),
+            storage: StorageLedger::new(b"storage".as_slice()),
+            arbitrator: None,
+            dispute_period: DEFAULT_DISPUTE_PERIOD,
+            challenge_bond_bps: DEFAULT_CHALLENGE_BOND_BPS,
+            accepted_tokens: UnorderedSet::new(b"accepted_tokens".as_slice()),
+            oracles: UnorderedSet::new(b"oracles".as_slice()),
+            bet_events: UnorderedMap::new(b"bet_events".as_slice()),
+            cross_chain_bets: UnorderedMap::new(b"cross_chain_bets".as_slice()),
+            unpaid_challenges: LookupMap::new(b"unpaid_challenges".as_slice()),
+            user_rewards: LookupMap::new(b"user_rewards".as_slice()),
+            ai_predictions: UnorderedMap::new(b"ai_predictions".as_slice()),
+            oracle_submissions: UnorderedMap::new(b"oracle_submissions".as_slice()),
+            bet_intents: UnorderedMap::new(b"bet_intents".as_slice()),
+            meta_orders: UnorderedMap::new(b"meta_orders".as_slice()),
+            solvers: UnorderedMap::new(b"solvers".as_slice()),
+            solver_reputation: LookupMap::new(b"solver_reputation".as_slice()),
+            quote_requests: UnorderedMap::new(b"quote_requests".as_slice()),
             order_counter: 0,
             quote_counter: 0,
         }
     }
 
-    // Create a new betting event
-    pub fn create_bet_event(&mut self, event_id: String, description: String, end_time: U64) {
+    // Grant `role` to an account (owner, or admin for roles other than admin)
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        self.access.grant_role(account_id, role);
+    }
+
+    // Revoke `role` from an account (owner, or admin for roles other than admin)
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        self.access.revoke_role(account_id, role);
+    }
+
+    // Hand the contract over to a new owner (owner only)
+    pub fn transfer_ownership(&mut self, new_owner_id: AccountId) {
+        self.access.transfer_ownership(new_owner_id);
+    }
+
+    // Pause `subsystem`, or the whole contract when `None` (pauser only)
+    pub fn pause(&mut self, subsystem: Option<Subsystem>) {
+        self.access.assert_role(Role::Pauser);
+        self.pause.pause(subsystem);
+    }
+
+    // Lift the pause of `subsystem`, or the global pause when `None` (pauser only)
+    pub fn unpause(&mut self, subsystem: Option<Subsystem>) {
+        self.access.assert_role(Role::Pauser);
+        self.pause.unpause(subsystem);
+    }
+
+    // NEP-145: add storage deposit for `account_id`, the caller by default
+    #[payable]
+    pub fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        self.storage.deposit(account_id, registration_only.unwrap_or(false))
+    }
+
+    // NEP-145: withdraw unused storage deposit, all of it when `amount` is `None`
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.storage.withdraw(amount)
+    }
+
+    // NEP-145: close the caller's storage account and refund its deposit. The account must not own
+    // any records, `force` can't remove them.
+    #[payable]
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.storage.unregister(force.unwrap_or(false))
+    }
+
+    // Whitelist a NEP-141 token for staking (admin only)
+    pub fn add_accepted_token(&mut self, token: AccountId) {
+        self.access.assert_role(Role::Admin);
+        self.accepted_tokens.insert(&token);
+        Event::TokenAccepted { token }.emit();
+    }
+
+    // Remove a NEP-141 token from the whitelist (admin only)
+    pub fn remove_accepted_token(&mut self, token: AccountId) {
+        self.access.assert_role(Role::Admin);
+        self.accepted_tokens.remove(&token);
+        Event::TokenRemoved { token }.emit();
+    }
+
+    // Register an oracle public key (`ed25519:` or `secp256k1:`) allowed to resolve events (admin only)
+    pub fn add_oracle(&mut self, public_key: String) {
+        self.access.assert_role(Role::Admin);
+        self.oracles.insert(&public_key);
+        Event::OracleAdded { public_key }.emit();
+    }
+
+    // Remove an oracle public key (admin only)
+    pub fn remove_oracle(&mut self, public_key: String) {
+        self.access.assert_role(Role::Admin);
+        self.oracles.remove(&public_key);
+        Event::OracleRemoved { public_key }.emit();
+    }
+
+    // Set the account that rules on challenges alongside admins (admin only)
+    pub fn set_arbitrator(&mut self, arbitrator: Option<AccountId>) {
+        self.access.assert_role(Role::Admin);
+        self.arbitrator = arbitrator;
+        Event::ArbitratorSet { arbitrator: self.arbitrator.clone() }.emit();
+    }
+
+    // Set the challenge window after a resolution and the bond it takes (admin only)
+    pub fn set_dispute_config(&mut self, dispute_period: U64, challenge_bond_bps: u32) {
+        self.access.assert_role(Role::Admin);
+        require!(challenge_bond_bps <= 10_000, "Invalid challenge bond");
+        self.dispute_period = dispute_period.0;
+        self.challenge_bond_bps = challenge_bond_bps;
+        Event::DisputeConfigUpdated { dispute_period, challenge_bond_bps }.emit();
+    }
+
+    // Create a new betting event, staked in `token` or native NEAR when `None`.
+    // Without `outcomes` the event is a binary No/Yes market, and without `quorum`
+    // a single report from any registered oracle resolves it.
+    pub fn create_bet_event(
+        &mut self,
+        event_id: String,
+        description: String,
+        end_time: U64,
+        token: Option<AccountId>,
+        outcomes: Option<Vec<String>>,
+        quorum: Option<OracleQuorum>,
+        min_confidence: Option<u64>,
+    ) {
+        self.access.assert_role(Role::MarketCreator);
+        if let Some(token) = &token {
+            require!(self.accepted_tokens.contains(token), "Token not accepted");
+        }
+        require!(self.bet_events.get(&event_id).is_none(), "Event already exists");
+        let outcomes = outcomes.unwrap_or_else(|| BINARY_OUTCOMES.iter().map(|label| label.to_string()).collect());
+        require!(outcomes.len() >= 2, "At least 2 outcomes required");
+        let quorum = quorum.unwrap_or_default();
+        self._assert_valid_quorum(&quorum);
+        let min_confidence = min_confidence.unwrap_or(0);
+        require!(min_confidence <= 100, "Invalid minimum confidence");
         let event = BetEvent {
             description,
+            token,
             end_time: end_time.0,
             resolved: false,
-            outcome: false,
+            pools: vec![0; outcomes.len()],
+            outcomes,
+            outcome: 0,
             total_bets: 0,
-            user_bets: LookupMap::new(b"user_bets"),
+            user_bets: LookupMap::new(Self::_user_bets_prefix(&event_id)),
+            quorum,
+            disputed: false,
+            dispute_end: 0,
+            challenge: None,
+            min_confidence,
+            resolution: "pending".to_string(),
         };
         
+        let initial_storage = env::storage_usage();
         self.bet_events.insert(&event_id, &event);
-        env::log_str(&format!("Bet event created: {}", event_id));
+        self.storage.settle(&env::predecessor_account_id(), initial_storage);
+        Event::BetEventCreated { event_id, token: event.token, end_time, outcomes: event.outcomes }.emit();
     }
 
-    // Place a bet on an event
-    pub fn place_bet(&mut self, event_id: String, amount: U128, outcome: bool) {
-        let mut event = self.bet_events.get(&event_id).expect("Event not found");
-        require!(!event.resolved, "Event already resolved");
-        require!(amount.0 > 0, "Amount must be greater than 0");
-        
-        // In a real implementation, you'd transfer tokens here
-        event.user_bets.insert(&env::predecessor_account_id(), &amount.0);
-        event.total_bets += amount.0;
-        
-        self.bet_events.insert(&event_id, &event);
-        
-        // Distribute rewards
-        let current_reward = self.user_rewards.get(&env::predecessor_account_id()).unwrap_or(0);
-        self.user_rewards.insert(&env::predecessor_account_id(), &(current_reward + amount.0 / 100));
-        
-        env::log_str(&format!("Bet placed: {} for event {}", amount.0, event_id));
+    // Place a bet on a native NEAR event with the attached deposit. The bet's records are charged
+    // to the bettor's storage deposit.
+    #[payable]
+    pub fn place_bet(&mut self, event_id: String, outcome: u32) {
+        self.pause.assert_not_paused(Subsystem::Betting);
+        let event = self.bet_events.get(&event_id).expect("Event not found");
+        require!(event.token.is_none(), "Event is staked in a NEP-141 token");
+
+        let amount = env::attached_deposit().as_yoctonear();
+        require!(self._can_bet(&event, amount, outcome), "Event not open for bets");
+        let initial_storage = env::storage_usage();
+        self._record_bet(&event_id, event, env::predecessor_account_id(), amount, outcome);
+        self.storage.settle(&env::predecessor_account_id(), initial_storage);
+    }
+
+    // Place a bet with NEP-141 tokens (called by the token contract via `ft_transfer_call`)
+    //
+    // `msg` is a JSON `BetMsg`. The whole amount is returned to the sender if the
+    // bet can't be placed, including when the sender isn't registered for storage.
+    pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        let token = env::predecessor_account_id();
+        require!(self.accepted_tokens.contains(&token), "Token not accepted");
+        let bet: BetMsg = serde_json::from_str(&msg).expect("Invalid bet message");
+
+        if bet.challenge {
+            return match self.bet_events.get(&bet.event_id) {
+                Some(event) if event.token.as_ref() == Some(&token) && self._can_challenge(&event, amount.0, bet.outcome) => {
+                    self._record_challenge(&bet.event_id, event, sender_id, amount.0, bet.outcome);
+                    PromiseOrValue::Value(U128(0))
+                }
+                _ => {
+                    Event::ChallengeRefused { event_id: bet.event_id.clone(), sender_id, amount }.emit();
+                    PromiseOrValue::Value(amount)
+                }
+            };
+        }
+
+        let event = match self.bet_events.get(&bet.event_id) {
+            Some(event) if !self.pause.is_paused(Subsystem::Betting) && event.token.as_ref() == Some(&token) && self._can_bet(&event, amount.0, bet.outcome) && self.storage.balance_of(&sender_id).is_some() => event,
+            _ => {
+                Event::BetRefused { event_id: bet.event_id.clone(), sender_id, amount }.emit();
+                return PromiseOrValue::Value(amount);
+            }
+        };
+        let initial_storage = env::storage_usage();
+        self._record_bet(&bet.event_id, event, sender_id.clone(), amount.0, bet.outcome);
+        self.storage.settle(&sender_id, initial_storage);
+
+        // All tokens are kept in the pool
+        PromiseOrValue::Value(U128(0))
     }
 
-    // Place a cross-chain bet
+    // Place a cross-chain bet on a native NEAR event. The attached deposit is the stake and is held
+    // like any other bet, so it is refunded through `claim_refund` if the event is voided.
+    #[payable]
     pub fn place_cross_chain_bet(
         &mut self,
         event_id: String,
         amount: U128,
-        outcome: bool,
+        outcome: u32,
         eth_address: String,
     ) -> String {
+        self.pause.assert_not_paused(Subsystem::Betting);
+        let event = self.bet_events.get(&event_id).expect("Event not found");
+        require!(event.token.is_none(), "Event is staked in a NEP-141 token");
+        require!(env::attached_deposit().as_yoctonear() == amount.0, "Attached deposit must match amount");
+        require!(self._can_bet(&event, amount.0, outcome), "Event not open for bets");
         let bet_id = format!("{}_{}_{}", event_id, env::predecessor_account_id(), env::block_timestamp());
-        
+        require!(self.cross_chain_bets.get(&bet_id).is_none(), "Bet already placed");
+        let initial_storage = env::storage_usage();
+        self._record_bet(&event_id, event, env::predecessor_account_id(), amount.0, outcome);
+
         let cross_chain_bet = CrossChainBet {
             user: env::predecessor_account_id(),
             event_id: event_id.clone(),
@@ -169,7 +536,7 @@ impl BetSwapAI {
         };
         
         self.cross_chain_bets.insert(&bet_id, &cross_chain_bet);
-        
+
         // Create bet intent for cross-chain swap
         let intent_id = format!("intent_{}", bet_id);
         let bet_intent = BetSwapIntent {
@@ -178,40 +545,232 @@ impl BetSwapAI {
             amount: amount.0,
             outcome,
             target_chain: "ETH".to_string(),
-            deadline: env::block_timestamp() + 3600, // 1 hour
+            deadline: env::block_timestamp() + BET_INTENT_TTL,
         };
         
         self.bet_intents.insert(&intent_id, &bet_intent);
+        self.storage.settle(&env::predecessor_account_id(), initial_storage);
         
-        env::log_str(&format!("Cross-chain bet placed: {}", bet_id));
+        Event::CrossChainBetPlaced {
+            bet_id: bet_id.clone(),
+            event_id: cross_chain_bet.event_id,
+            account_id: cross_chain_bet.user,
+            outcome,
+            amount,
+            eth_address: cross_chain_bet.eth_address,
+        }
+        .emit();
         bet_id
     }
 
-    // AI-driven outcome prediction
-    pub fn predict_outcome_with_ai(&mut self, event_id: String, oracle_data: String) -> AIOutcomePrediction {
-        // Simulate AI analysis of oracle data
-        let predicted_outcome = self._analyze_oracle_data(&oracle_data);
-        let confidence = self._calculate_confidence(&oracle_data);
-        
+    // Submit a signed oracle report (`oracle_data` is a JSON `OracleReport`). The event
+    // resolves once `quorum.threshold` oracles agree, with the median confidence of the
+    // agreeing reports. More than `quorum.dispute_margin` dissenting reports, or two outcomes tied at
+    // the threshold, mark it disputed.
+    // A median below `min_confidence` is only advisory and leaves the event for `resolve_event`.
+    // Reports for `VOID_OUTCOME` count like any outcome and void the event on reaching the threshold.
+    pub fn predict_outcome_with_ai(&mut self, event_id: String, oracle_data: String) -> Option<AIOutcomePrediction> {
+        self.access.assert_role(Role::Oracle);
+        let mut event = self.bet_events.get(&event_id).expect("Event not found");
+        require!(!event.resolved, "Event already resolved");
+        require!(!event.disputed, "Event disputed");
+        require!(event.resolution != "needs_review", "Event needs review");
+        require!(event.resolution != "void", "Event void");
+        require!(env::block_timestamp() >= event.end_time, "Event not ended");
+
+        let report: OracleReport = serde_json::from_str(&oracle_data).expect("Invalid oracle report");
+        require!(report.event_id == event_id, "Report is for another event");
+        require!(report.timestamp.0 >= event.end_time && report.timestamp.0 <= env::block_timestamp(), "Invalid report timestamp");
+        require!(report.outcome == VOID_OUTCOME || (report.outcome as usize) < event.outcomes.len(), "Invalid outcome");
+        require!(report.confidence <= 100, "Invalid confidence");
+        require!(
+            event.quorum.oracles.is_empty() || event.quorum.oracles.contains(&report.public_key),
+            "Oracle not in event quorum"
+        );
+        require!(self._verify_oracle_report(&report), "Invalid oracle signature");
+
+        let mut submissions = self.oracle_submissions.get(&event_id).unwrap_or_default();
+        require!(submissions.iter().all(|submission| submission.public_key != report.public_key), "Oracle already reported");
+        submissions.push(OracleSubmission {
+            public_key: report.public_key,
+            outcome: report.outcome,
+            confidence: report.confidence,
+            timestamp: report.timestamp,
+            report: oracle_data,
+        });
+        self.oracle_submissions.insert(&event_id, &submissions);
+
+        // Tally the reports, void votes last
+        let mut votes = vec![0u32; event.outcomes.len() + 1];
+        for submission in &submissions {
+            let index = if submission.outcome == VOID_OUTCOME { event.outcomes.len() } else { submission.outcome as usize };
+            votes[index] += 1;
+        }
+        // Ties go to the lowest index, so a void vote never beats a real outcome
+        let (leader, leader_votes) = votes.iter().copied().enumerate().max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0))).unwrap();
+        let dissent = submissions.len() as u32 - leader_votes;
+        let tied = votes.iter().filter(|count| **count == leader_votes).count() > 1;
+
+        let latest = submissions.last().unwrap();
+        Event::OracleReported {
+            event_id: event_id.clone(),
+            public_key: latest.public_key.clone(),
+            outcome: latest.outcome,
+            confidence: latest.confidence,
+            leading_votes: leader_votes,
+            threshold: event.quorum.threshold,
+        }
+        .emit();
+
+        if dissent > event.quorum.dispute_margin || (tied && leader_votes >= event.quorum.threshold) {
+            event.disputed = true;
+            self.bet_events.insert(&event_id, &event);
+            Event::EventDisputed { event_id, dissenting_reports: dissent }.emit();
+            return None;
+        }
+        if leader_votes < event.quorum.threshold {
+            return None;
+        }
+        if leader == event.outcomes.len() {
+            self._void_event(&event_id, event);
+            return None;
+        }
+
+        let predicted_outcome = leader as u32;
+        let agreeing: Vec<&OracleSubmission> = submissions.iter().filter(|submission| submission.outcome == predicted_outcome).collect();
+        let confidence = Self::_median(agreeing.iter().map(|submission| submission.confidence).collect());
+        let reports: Vec<&String> = agreeing.iter().map(|submission| &submission.report).collect();
+
         let prediction = AIOutcomePrediction {
             event_id: event_id.clone(),
             predicted_outcome,
             confidence,
-            oracle_data,
+            oracle_data: serde_json::to_string(&reports).unwrap(),
             timestamp: env::block_timestamp(),
+            advisory: confidence < event.min_confidence,
         };
         
         self.ai_predictions.insert(&event_id, &prediction);
         
-        // Resolve the bet event
-        if let Some(mut event) = self.bet_events.get(&event_id) {
-            event.resolved = true;
-            event.outcome = predicted_outcome;
+        if prediction.advisory {
+            // Too uncertain to settle on its own, an authorised resolver has to decide
+            event.resolution = "needs_review".to_string();
             self.bet_events.insert(&event_id, &event);
+            Event::PredictionAdvisory {
+                event_id,
+                outcome: predicted_outcome,
+                confidence,
+                min_confidence: event.min_confidence,
+            }
+            .emit();
+            return Some(prediction);
         }
+
+        // Provisionally resolve the bet event, open for challenges until `dispute_end`
+        event.resolved = true;
+        event.outcome = predicted_outcome;
+        event.dispute_end = env::block_timestamp() + self.dispute_period;
+        event.resolution = "auto".to_string();
+        self.bet_events.insert(&event_id, &event);
         
-        env::log_str(&format!("AI prediction: {} -> {} (confidence: {})", event_id, predicted_outcome, confidence));
-        prediction
+        Event::EventResolved {
+            event_id,
+            outcome: predicted_outcome,
+            confidence: Some(confidence),
+            resolution: event.resolution,
+        }
+        .emit();
+        Some(prediction)
+    }
+
+    // Challenge a provisional resolution, bonding the attached deposit on `outcome`
+    #[payable]
+    pub fn challenge_outcome(&mut self, event_id: String, outcome: u32) {
+        let event = self.bet_events.get(&event_id).expect("Event not found");
+        require!(event.token.is_none(), "Event is staked in a NEP-141 token");
+
+        let bond = env::attached_deposit().as_yoctonear();
+        require!(self._can_challenge(&event, bond, outcome), "Cannot challenge event");
+        self._record_challenge(&event_id, event, env::predecessor_account_id(), bond, outcome);
+    }
+
+    // Rule on a pending challenge (admin or arbitrator). An upheld challenge switches the
+    // outcome and returns the bond plus an equal reward from the losing stake. If nobody backed
+    // the upheld outcome there is no reward, as every stake is refunded through `claim_refund`.
+    // A rejected challenge's bond is added to the pool, or returned when nobody backed the outcome, as
+    // refunds only repay stakes. Either way the resolution becomes final.
+    pub fn rule_on_challenge(&mut self, event_id: String, upheld: bool) {
+        let caller = env::predecessor_account_id();
+        require!(self.access.has_role(&caller, Role::Admin) || Some(&caller) == self.arbitrator.as_ref(), "Only admin or arbitrator can rule");
+
+        let mut event = self.bet_events.get(&event_id).expect("Event not found");
+        let challenge = event.challenge.take().expect("No pending challenge");
+
+        if upheld {
+            event.outcome = challenge.outcome;
+            let losing_pool = event.total_bets - event.pool(event.outcome);
+            let reward = if event.pool(event.outcome) == 0 { 0 } else { challenge.bond.0.min(losing_pool) };
+            event.total_bets -= reward;
+            Self::_pay_challenger(event_id.clone(), &event.token, challenge.challenger.clone(), challenge.bond.0 + reward);
+        } else if event.pool(event.outcome) == 0 {
+            Self::_pay_challenger(event_id.clone(), &event.token, challenge.challenger.clone(), challenge.bond.0);
+        } else {
+            event.total_bets += challenge.bond.0;
+        }
+        event.dispute_end = env::block_timestamp();
+        self.bet_events.insert(&event_id, &event);
+
+        Event::ChallengeRuled { event_id, upheld, outcome: event.outcome }.emit();
+    }
+
+    // Send a challenger payout again after its transfer failed. Anyone can call this, the
+    // payout always goes to the challenger.
+    pub fn retry_challenge_payout(&mut self, event_id: String) -> Promise {
+        let (challenger, amount) = self.unpaid_challenges.get(&event_id).expect("No failed challenge payout");
+        self.unpaid_challenges.remove(&event_id);
+        let event = self.bet_events.get(&event_id).expect("Event not found");
+        Self::_pay_challenger(event_id, &event.token, challenger, amount)
+    }
+
+    // Keep a failed challenger payout for `retry_challenge_payout`
+    #[private]
+    pub fn on_challenge_payout_transferred(&mut self, event_id: String, challenger: AccountId, amount: U128) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => true,
+            _ => {
+                self.unpaid_challenges.insert(&event_id, &(challenger.clone(), amount.0));
+                Event::PayoutFailed { event_id, account_id: challenger }.emit();
+                false
+            }
+        }
+    }
+
+    // Settle an event the oracles could not, either below its minimum confidence or disputed
+    pub fn resolve_event(&mut self, event_id: String, outcome: u32) {
+        let caller = env::predecessor_account_id();
+        require!(self.access.has_role(&caller, Role::Admin) || Some(&caller) == self.arbitrator.as_ref(), "Only admin or arbitrator can resolve");
+
+        let mut event = self.bet_events.get(&event_id).expect("Event not found");
+        require!(!event.resolved, "Event already resolved");
+        require!(event.resolution != "void", "Event void");
+        require!(event.resolution == "needs_review" || event.disputed, "Event does not need review");
+        require!((outcome as usize) < event.outcomes.len(), "Invalid outcome");
+
+        // A resolver's ruling is final, claims open immediately
+        event.resolved = true;
+        event.outcome = outcome;
+        event.dispute_end = env::block_timestamp();
+        event.resolution = "manual".to_string();
+        self.bet_events.insert(&event_id, &event);
+
+        Event::EventResolved { event_id, outcome, confidence: None, resolution: event.resolution }.emit();
+    }
+
+    // Cancel an ambiguous, postponed or mis-specified event so every bettor can reclaim their stake
+    pub fn void_event(&mut self, event_id: String) {
+        self.access.assert_role(Role::Admin);
+        let event = self.bet_events.get(&event_id).expect("Event not found");
+        self._void_event(&event_id, event);
     }
 
     // Register a solver for cross-chain swaps
@@ -223,6 +782,7 @@ impl BetSwapAI {
         fee_percentage: U64,
         tee_config: TEEConfig,
     ) {
+        self.access.assert_role(Role::SolverManager);
         let config = SolverConfig {
             solver_address: solver_address.clone(),
             min_quote_amount: min_quote_amount.0,
@@ -234,7 +794,7 @@ impl BetSwapAI {
         self.solvers.insert(&solver_address, &config);
         self.solver_reputation.insert(&solver_address, &100); // Initial reputation
         
-        env::log_str(&format!("Solver registered: {}", solver_address));
+        Event::SolverRegistered { solver_id: solver_address }.emit();
     }
 
     // Request a quote for cross-chain bet swap
@@ -245,87 +805,309 @@ impl BetSwapAI {
         from_amount: U128,
         deadline: U64,
     ) -> String {
+        self.pause.assert_not_paused(Subsystem::Quoting);
         self.quote_counter += 1;
         let request_id = format!("quote_{}", self.quote_counter);
         
-        let quote_request = format!("{}:{}:{}:{}", from_token, to_token, from_amount.0, deadline.0);
+        let quote_request = BetQuoteRequest {
+            requester: env::predecessor_account_id(),
+            from_token,
+            to_token,
+            from_amount,
+            deadline,
+        };
+        let initial_storage = env::storage_usage();
         self.quote_requests.insert(&request_id, &quote_request);
+        self.storage.settle(&quote_request.requester, initial_storage);
         
-        env::log_str(&format!("Quote requested: {}", request_id));
+        Event::QuoteRequested {
+            request_id: request_id.clone(),
+            requester: quote_request.requester,
+            from_token: quote_request.from_token,
+            to_token: quote_request.to_token,
+            from_amount,
+            deadline,
+        }
+        .emit();
         request_id
     }
 
+    // Remove a bet swap quote request past its deadline, crediting the storage back to the requester.
+    // Anyone can call this.
+    pub fn remove_expired_bet_swap_quote(&mut self, request_id: String) {
+        let quote_request = self.quote_requests.get(&request_id).expect("Quote request not found");
+        require!(env::block_timestamp() > quote_request.deadline.0, "Quote not expired");
+
+        let initial_storage = env::storage_usage();
+        self.quote_requests.remove(&request_id);
+        self.storage.settle(&quote_request.requester, initial_storage);
+        Event::QuoteRemoved { request_id }.emit();
+    }
+
     // Generate quote and create meta-order (called by solver)
     pub fn generate_bet_swap_quote(
         &mut self,
         request_id: String,
         to_amount: U128,
         intent_id: String,
-        signature: String,
+        signature: ChainSignature,
     ) -> String {
+        self.pause.assert_not_paused(Subsystem::Quoting);
+        let solver = self.solvers.get(&env::predecessor_account_id()).expect("Solver not registered");
+        require!(signature.public_key == solver.tee_config.public_key, "Signature key does not match solver key");
+        let quote_request = self.quote_requests.get(&request_id).expect("Quote request not found");
+        require!(env::block_timestamp() <= quote_request.deadline.0, "Quote expired");
+        let intent = self.bet_intents.get(&intent_id).expect("Intent not found");
+
         self.order_counter += 1;
         let order_id = format!("order_{}", self.order_counter);
         
         let meta_order = MetaOrder {
             order_id: order_id.clone(),
             user: env::predecessor_account_id(),
-            event_id: "".to_string(), // Will be set from intent
+            event_id: intent.event_id,
             amount: to_amount.0,
-            outcome: false,
+            outcome: intent.outcome,
             intent_id,
             signature,
             is_executed: false,
         };
         
+        let initial_storage = env::storage_usage();
         self.meta_orders.insert(&order_id, &meta_order);
+        self.storage.settle(&env::predecessor_account_id(), initial_storage);
         
         // Update solver reputation
         let current_reputation = self.solver_reputation.get(&env::predecessor_account_id()).unwrap_or(0);
         self.solver_reputation.insert(&env::predecessor_account_id(), &(current_reputation + 10));
         
-        env::log_str(&format!("Meta-order created: {}", order_id));
+        Event::QuoteGenerated {
+            request_id,
+            order_id: order_id.clone(),
+            solver_id: env::predecessor_account_id(),
+            to_amount,
+        }
+        .emit();
         order_id
     }
 
     // Execute meta-order
     pub fn execute_bet_meta_order(&mut self, order_id: String, secret: String) {
-        if let Some(mut order) = self.meta_orders.get(&order_id) {
-            // Verify NEAR Chain Signature (mocked)
-            require!(self._verify_near_signature(&order.signature, &order.intent_id), "Invalid signature");
-            
-            order.is_executed = true;
-            self.meta_orders.insert(&order_id, &order);
-            
-            // Update solver reputation
-            let current_reputation = self.solver_reputation.get(&env::predecessor_account_id()).unwrap_or(0);
-            self.solver_reputation.insert(&env::predecessor_account_id(), &(current_reputation + 20));
-            
-            env::log_str(&format!("Meta-order executed: {}", order_id));
+        self.pause.assert_not_paused(Subsystem::Execution);
+        require!(self.solvers.get(&env::predecessor_account_id()).is_some(), "Solver not registered");
+        let mut order = self.meta_orders.get(&order_id).expect("Order not found");
+        require!(!order.is_executed, "Order already executed");
+        require!(env::predecessor_account_id() == order.user, "Only quoting solver can execute");
+
+        // Verify NEAR Chain Signature
+        require!(self._verify_near_signature(&order), "Invalid signature");
+        let preimage = hex::decode(&secret).expect("Invalid secret");
+        
+        order.is_executed = true;
+        self.meta_orders.insert(&order_id, &order);
+        
+        // Update solver reputation
+        let current_reputation = self.solver_reputation.get(&env::predecessor_account_id()).unwrap_or(0);
+        self.solver_reputation.insert(&env::predecessor_account_id(), &(current_reputation + 20));
+        
+        Event::MetaOrderExecuted {
+            order_id,
+            solver_id: env::predecessor_account_id(),
+            success: true,
+            hashlock: Some(hex::encode(env::sha256(&preimage))),
+        }
+        .emit();
+    }
+
+    // Remove a bet meta-order once executed, or once it can no longer be executed because its intent is
+    // gone or past its deadline, crediting the storage back to the quoting solver. Anyone can call this.
+    pub fn remove_bet_meta_order(&mut self, order_id: String) {
+        let order = self.meta_orders.get(&order_id).expect("Order not found");
+        let expired = self.bet_intents.get(&order.intent_id).is_none_or(|intent| env::block_timestamp() > intent.deadline);
+        require!(order.is_executed || expired, "Order not finished");
+
+        let initial_storage = env::storage_usage();
+        self.meta_orders.remove(&order_id);
+        self.storage.settle(&order.user, initial_storage);
+        Event::MetaOrderRemoved { order_id }.emit();
+    }
+
+    // Claim the winnings of a resolved event: the winning stake plus its pro-rata
+    // share of the losing pool. The stake's storage is credited back once the transfer succeeds.
+    pub fn claim_winnings(&mut self, event_id: String) -> Promise {
+        self.pause.assert_not_paused(Subsystem::Claims);
+        let mut event = self.bet_events.get(&event_id).expect("Event not found");
+        require!(event.resolved, "Event not resolved");
+        require!(event.challenge.is_none() && env::block_timestamp() >= event.dispute_end, "Resolution still open to challenges");
+
+        let user = env::predecessor_account_id();
+        let mut stake = event.user_bets.get(&user).expect("No bet placed");
+        require!(!stake.claimed, "Winnings already claimed");
+
+        let winning_stake = stake.on(event.outcome);
+        require!(winning_stake > 0, "No winnings to claim");
+        let losing_pool = event.total_bets - event.pool(event.outcome);
+        let payout = winning_stake + mul_div(winning_stake, losing_pool, event.pool(event.outcome));
+
+        stake.claimed = true;
+        event.user_bets.insert(&user, &stake);
+
+        Event::WinningsClaimed { event_id: event_id.clone(), account_id: user.clone(), amount: U128(payout) }.emit();
+
+        Self::_transfer(&event.token, user.clone(), payout, format!("Winnings {}", event_id)).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_CLAIM)
+                .on_winnings_transferred(event_id, user),
+        )
+    }
+
+    // Remove a settled cross-chain bet and its intent, crediting the storage back to the bettor. A bet is
+    // settled once completed, once its event is void, or once its event is resolved and past the dispute
+    // window. Anyone can call this; the stake itself is paid out through `claim_winnings` or `claim_refund`.
+    pub fn remove_cross_chain_bet(&mut self, bet_id: String) {
+        let bet = self.cross_chain_bets.get(&bet_id).expect("Bet not found");
+        let settled = bet.completed
+            || self.bet_events.get(&bet.event_id).is_some_and(|event| {
+                event.resolution == "void"
+                    || (event.resolved && event.challenge.is_none() && env::block_timestamp() >= event.dispute_end)
+            });
+        require!(settled, "Bet not settled");
+
+        let initial_storage = env::storage_usage();
+        self.cross_chain_bets.remove(&bet_id);
+        self.bet_intents.remove(&format!("intent_{}", bet_id));
+        self.storage.settle(&bet.user, initial_storage);
+        Event::CrossChainBetRemoved { bet_id }.emit();
+    }
+
+    // Refund the exact stake placed on a void event, or on a final outcome nobody backed, without fees or
+    // rewards. Never paused.
+    pub fn claim_refund(&mut self, event_id: String) -> Promise {
+        let mut event = self.bet_events.get(&event_id).expect("Event not found");
+        let unbacked = event.resolved && event.pool(event.outcome) == 0 && event.challenge.is_none() && env::block_timestamp() >= event.dispute_end;
+        require!(event.resolution == "void" || unbacked, "Event not refundable");
+
+        let user = env::predecessor_account_id();
+        let mut stake = event.user_bets.get(&user).expect("No bet placed");
+        require!(!stake.claimed, "Refund already claimed");
+        let refund: u128 = stake.stakes.iter().sum();
+
+        stake.claimed = true;
+        event.user_bets.insert(&user, &stake);
+
+        Event::RefundClaimed { event_id: event_id.clone(), account_id: user.clone(), amount: U128(refund) }.emit();
+
+        Self::_transfer(&event.token, user.clone(), refund, format!("Refund {}", event_id)).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_CLAIM)
+                .on_winnings_transferred(event_id, user),
+        )
+    }
+
+    // Free the paid stake's storage, or allow the claim or refund to be retried if the transfer failed
+    #[private]
+    pub fn on_winnings_transferred(&mut self, event_id: String, user: AccountId) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                if let Some(mut event) = self.bet_events.get(&event_id) {
+                    let initial_storage = env::storage_usage();
+                    event.user_bets.remove(&user);
+                    self.storage.settle(&user, initial_storage);
+                }
+                true
+            }
+            _ => {
+                if let Some(mut event) = self.bet_events.get(&event_id) {
+                    if let Some(mut stake) = event.user_bets.get(&user) {
+                        stake.claimed = false;
+                        event.user_bets.insert(&user, &stake);
+                    }
+                }
+                Event::PayoutFailed { event_id, account_id: user }.emit();
+                false
+            }
         }
     }
 
-    // Claim rewards
+    // Claim rewards, freeing the storage of the rewards entry
     pub fn claim_rewards(&mut self) -> U128 {
+        self.pause.assert_not_paused(Subsystem::Claims);
         let reward = self.user_rewards.get(&env::predecessor_account_id()).unwrap_or(0);
         require!(reward > 0, "No rewards to claim");
         
-        self.user_rewards.insert(&env::predecessor_account_id(), &0);
+        let initial_storage = env::storage_usage();
+        self.user_rewards.remove(&env::predecessor_account_id());
+        self.storage.settle(&env::predecessor_account_id(), initial_storage);
         
-        env::log_str(&format!("Rewards claimed: {}", reward));
+        Event::RewardsClaimed { account_id: env::predecessor_account_id(), amount: U128(reward) }.emit();
         U128(reward)
     }
 
     // Getter functions
-    pub fn get_bet_event(&self, event_id: String) -> Option<BetEvent> {
-        self.bet_events.get(&event_id)
+    pub fn get_bet_event(&self, event_id: String) -> Option<BetEventView> {
+        self.bet_events.get(&event_id).map(BetEventView::from)
     }
 
-    pub fn get_user_bet(&self, event_id: String, user: AccountId) -> U128 {
-        if let Some(event) = self.bet_events.get(&event_id) {
-            U128(event.user_bets.get(&user).unwrap_or(0))
-        } else {
-            U128(0)
-        }
+    // Bet events with their ids, optionally only those with `status` (see `BetEvent::status`)
+    pub fn get_bet_events(&self, status: Option<String>, from_index: Option<U64>, limit: Option<U64>) -> Vec<(String, BetEventView)> {
+        let events = self
+            .bet_events
+            .iter()
+            .filter(|(_, event)| status.as_deref().is_none_or(|status| event.status() == status))
+            .map(|(event_id, event)| (event_id, BetEventView::from(event)));
+        paginate(events, from_index, limit)
+    }
+
+    pub fn get_user_bet(&self, event_id: String, user: AccountId) -> Option<UserStake> {
+        self.bet_events.get(&event_id).and_then(|event| event.user_bets.get(&user))
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.access.owner_id.clone()
+    }
+
+    pub fn get_roles(&self, account_id: AccountId) -> Vec<Role> {
+        self.access.roles_of(&account_id)
+    }
+
+    pub fn has_role(&self, account_id: AccountId, role: Role) -> bool {
+        self.access.has_role(&account_id, role)
+    }
+
+    pub fn get_pause_state(&self) -> PauseState {
+        self.pause.clone()
+    }
+
+    // NEP-145: storage balance of an account, if registered
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage.balance_of(&account_id)
+    }
+
+    // NEP-145: minimum deposit to register and no maximum
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageLedger::bounds()
+    }
+
+    pub fn get_arbitrator(&self) -> Option<AccountId> {
+        self.arbitrator.clone()
+    }
+
+    // Bond currently required to challenge an event's resolution
+    pub fn get_challenge_bond(&self, event_id: String) -> U128 {
+        let event = self.bet_events.get(&event_id).expect("Event not found");
+        U128(self._challenge_bond(&event))
+    }
+
+    pub fn get_oracle_submissions(&self, event_id: String) -> Vec<OracleSubmission> {
+        self.oracle_submissions.get(&event_id).unwrap_or_default()
+    }
+
+    pub fn get_oracles(&self) -> Vec<String> {
+        self.oracles.to_vec()
+    }
+
+    pub fn get_accepted_tokens(&self) -> Vec<AccountId> {
+        self.accepted_tokens.to_vec()
     }
 
     pub fn get_user_rewards(&self, user: AccountId) -> U128 {
@@ -341,28 +1123,1596 @@ impl BetSwapAI {
     }
 
     // Internal helper functions
-    fn _analyze_oracle_data(&self, oracle_data: &String) -> bool {
-        // Simulate AI analysis - in production, this would use actual ML models
-        // For demo, we'll use a simple hash-based prediction
-        let hash = env::sha256(oracle_data.as_bytes());
-        hash[0] % 2 == 0
+
+    // Storage prefix of an event's `user_bets`, unique per event id
+    fn _user_bets_prefix(event_id: &str) -> Vec<u8> {
+        [b"user_bets".as_slice(), &env::sha256(event_id.as_bytes())].concat()
     }
 
-    fn _calculate_confidence(&self, oracle_data: &String) -> u64 {
-        // Simulate confidence calculation
-        let hash = env::sha256(oracle_data.as_bytes());
-        ((hash[1] as u64) * 100) / 255
+    fn _can_bet(&self, event: &BetEvent, amount: u128, outcome: u32) -> bool {
+        !event.resolved && event.resolution != "void" && env::block_timestamp() < event.end_time && amount > 0 && (outcome as usize) < event.outcomes.len()
     }
 
-    fn _verify_near_signature(&self, signature: &String, intent_id: &String) -> bool {
-        // Mock signature verification - in production, this would verify actual NEAR Chain Signatures
-        signature.len() > 0 && intent_id.len() > 0
+    // Move an unresolved event to the terminal "void" state. Constant cost regardless of the number of bets:
+    // cross-chain stakes are held in the event pools and refunded through `claim_refund`, and their swap
+    // intents are dropped one at a time through `remove_cross_chain_bet`.
+    fn _void_event(&mut self, event_id: &str, mut event: BetEvent) {
+        require!(!event.resolved, "Event already resolved");
+        require!(event.resolution != "void", "Event already void");
+        event.resolution = "void".to_string();
+        self.bet_events.insert(&event_id.to_string(), &event);
+
+        Event::EventVoided { event_id: event_id.to_string() }.emit();
+    }
+
+    fn _challenge_bond(&self, event: &BetEvent) -> u128 {
+        (event.total_bets * self.challenge_bond_bps as u128 / 10_000).max(1)
+    }
+
+    fn _can_challenge(&self, event: &BetEvent, bond: u128, outcome: u32) -> bool {
+        event.resolved
+            && event.challenge.is_none()
+            && env::block_timestamp() < event.dispute_end
+            && outcome != event.outcome
+            && (outcome as usize) < event.outcomes.len()
+            && bond == self._challenge_bond(event)
+    }
+
+    fn _record_challenge(&mut self, event_id: &String, mut event: BetEvent, challenger: AccountId, bond: u128, outcome: u32) {
+        event.challenge = Some(Challenge {
+            challenger: challenger.clone(),
+            outcome,
+            bond: U128(bond),
+            created_at: U64(env::block_timestamp()),
+        });
+        self.bet_events.insert(event_id, &event);
+
+        Event::OutcomeChallenged { event_id: event_id.clone(), challenger, outcome, bond: U128(bond) }.emit();
+    }
+
+    // Send `amount` of the event's stake token (or NEAR) to `receiver_id`
+    fn _transfer(token: &Option<AccountId>, receiver_id: AccountId, amount: u128, memo: String) -> Promise {
+        match token {
+            Some(token) => ext_ft::ext(token.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(receiver_id, U128(amount), Some(memo)),
+            None => Promise::new(receiver_id).transfer(NearToken::from_yoctonear(amount)),
+        }
+    }
+
+    fn _pay_challenger(event_id: String, token: &Option<AccountId>, challenger: AccountId, amount: u128) -> Promise {
+        Self::_transfer(token, challenger.clone(), amount, format!("Challenge {}", event_id)).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_CLAIM)
+                .on_challenge_payout_transferred(event_id, challenger, U128(amount)),
+        )
     }
-}
 
-// Helper function for requirements
-fn require!(condition: bool, message: &str) {
-    if !condition {
-        env::panic_str(message);
+    fn _record_bet(&mut self, event_id: &String, mut event: BetEvent, bettor: AccountId, amount: u128, outcome: u32) {
+        let mut stake = event.user_bets.get(&bettor).unwrap_or_default();
+        stake.stakes.resize(event.outcomes.len(), 0);
+        stake.stakes[outcome as usize] += amount;
+        event.pools[outcome as usize] += amount;
+        event.user_bets.insert(&bettor, &stake);
+        event.total_bets += amount;
+
+        self.bet_events.insert(event_id, &event);
+
+        // Distribute rewards
+        let current_reward = self.user_rewards.get(&bettor).unwrap_or(0);
+        self.user_rewards.insert(&bettor, &(current_reward + amount / 100));
+
+        Event::BetPlaced { event_id: event_id.clone(), account_id: bettor, outcome, amount: U128(amount) }.emit();
+    }
+
+    fn _assert_valid_quorum(&self, quorum: &OracleQuorum) {
+        require!(quorum.threshold > 0, "Threshold must be greater than 0");
+        // Otherwise a rival outcome could also reach the threshold without disputing the event
+        require!(quorum.dispute_margin < quorum.threshold, "Dispute margin must be below threshold");
+        if !quorum.oracles.is_empty() {
+            require!(quorum.threshold as usize <= quorum.oracles.len(), "Threshold exceeds oracle count");
+            for (i, oracle) in quorum.oracles.iter().enumerate() {
+                require!(self.oracles.contains(oracle), "Oracle not registered");
+                require!(!quorum.oracles[..i].contains(oracle), "Duplicate oracle");
+            }
+        }
+    }
+
+    fn _median(mut values: Vec<u64>) -> u64 {
+        values.sort_unstable();
+        let mid = values.len() / 2;
+        if values.len().is_multiple_of(2) {
+            (values[mid - 1] + values[mid]) / 2
+        } else {
+            values[mid]
+        }
+    }
+
+    fn _verify_oracle_report(&self, report: &OracleReport) -> bool {
+        let message = signature::oracle_report_message(&report.event_id, report.outcome, report.confidence, report.timestamp.0);
+        self.oracles.contains(&report.public_key) && signature::verify_signature(&report.signature, &report.public_key, &message)
     }
-} 
\ No newline at end of file
+
+    fn _verify_near_signature(&self, order: &MetaOrder) -> bool {
+        // The key must be the one the quoting solver registered
+        let solver_key = self.solvers.get(&order.user).map(|solver| solver.tee_config.public_key);
+        if solver_key.as_ref() != Some(&order.signature.public_key) {
+            return false;
+        }
+        // The signed message must commit to the bet intent and the quoted amount
+        let intent = match self.bet_intents.get(&order.intent_id) {
+            Some(intent) => intent,
+            None => return false,
+        };
+        let expected_message = signature::order_message(&order.intent_id, intent.amount, order.amount, intent.deadline);
+        order.signature.message == expected_message
+            && signature::verify_signature(&order.signature.signature, &order.signature.public_key, &order.signature.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    fn owner() -> AccountId {
+        "owner.testnet".parse().unwrap()
+    }
+
+    fn alice() -> AccountId {
+        "alice.testnet".parse().unwrap()
+    }
+
+    fn set_time(predecessor: &AccountId, timestamp: u64) {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(predecessor.clone()).block_timestamp(timestamp);
+        testing_env!(context.build());
+    }
+
+    fn set_context(predecessor: &AccountId, deposit: u128) {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(predecessor.clone()).attached_deposit(NearToken::from_yoctonear(deposit));
+        testing_env!(context.build());
+    }
+
+    fn bob() -> AccountId {
+        "bob.testnet".parse().unwrap()
+    }
+
+    fn carol() -> AccountId {
+        "carol.testnet".parse().unwrap()
+    }
+
+    fn usdc() -> AccountId {
+        "usdc.testnet".parse().unwrap()
+    }
+
+    const ORACLE_KEY: &str = "ed25519:GyGKxMyg1p9SsHfm15MkNUu1u9TN2JtTspcdmrtGUdse";
+    const ORACLE_KEY_2: &str = "ed25519:EdmxWPmx2WH6WgFfTdu9xfkYf3k1g5wD1zccTVySEEh1";
+    const ORACLE_KEY_3: &str = "ed25519:8SFqwqnq4whPhs8icwHA2hQg3hUoN1qrCLK1SBx3WKwe";
+
+    /// Signed reports on `event_a` at timestamp 2000: (key, outcome, confidence, signature)
+    const REPORTS: [(&str, u32, u64, &str); 8] = [
+        (ORACLE_KEY, 1, 90, "cbc061cdb1a305003ec369b5cf8eb93a85000363cb1b04f7f49ed6d1958493b36e00c2ccc4bc51d75de1dbd2b490ac625fb815fd1f087d97d499d33b89f4720f"),
+        (ORACLE_KEY, 0, 80, "8cd3261187bc5d136af8194b743d98fa3b6f44bf7132b09dc8aa5a38f11b53e7c0c60f7ea8d493612eae6e0f903122b92101a541bb48caa4412b4566f4906b08"),
+        (ORACLE_KEY_2, 1, 70, "64da04b6b827a9cd0938d7351599bbd9da0bc63afaae6fcfb6c90a6ded9ae5b72269ec2dc165ebc13537175ea2aaba3993435601fc2fa1675494735e3a1bdb05"),
+        (ORACLE_KEY_2, 0, 60, "c38a5d7718b8a59ce68fb2d3e8c20be88904cf3c0b58ec402eccc6d191b991805028e6c8110c43149e42f4f2e47fa8cef22dd3e286d8675a71d73e0fc7182202"),
+        (ORACLE_KEY_3, 1, 50, "ca0d5e7d2c7e5d6e7aad973506a1e940fd079f9f39750975bd5919de8106dd174ed49151898ff2b902f1f7ecf7564532764cc82efa5abf3f0c758ef784ba4800"),
+        (ORACLE_KEY_3, 0, 40, "fdd72bb956e5856796ea6642bfdd8d04155b5199acb3735e8f40f03992d7ed9eee64bf07f5c99a4c3bb10a441f294d5f02d07aa60f8aadf5c6bcb33e318bc707"),
+        (ORACLE_KEY, VOID_OUTCOME, 95, "303247227dfbeb722335d79f0483062646ca81336f2fc3802d7e64dfb2c6eaa9a6b4e14296c90ada58c3222cde26e33ca358439dc026ae320fe534085041ed02"),
+        (ORACLE_KEY_2, VOID_OUTCOME, 85, "611d901badc5ed16c20d5f44a209581d94d0df01640da1513140e79ea0acd89a3f99dc9cafbda17e7c1f4cef995cf04c6fcf698d2e36f40fe42c1a27fefaae0a"),
+    ];
+
+    const MESSAGE: &str = "intent_1:1000000:990000:1700000000000000000";
+    const ED25519_KEY: &str = "ed25519:8ocU7FbgmTDA4egSLPguXXPzm9MAfyPVP7m39Yc6ji6G";
+    const ED25519_SIGNATURE: &str = "69e81a9d312cedaff16f5c1630e01cd9d46d791a27bdc48cd2a21f3d3b11a604776be14e648bd0f152a30b7509bc3d080610ce755fbb1e252e854ba2069ea000";
+
+    /// Deposits 0.1 NEAR of storage for `account_id`, leaving it as the predecessor
+    fn deposit_storage(contract: &mut BetSwapAI, account_id: &AccountId) {
+        set_context(account_id, NearToken::from_millinear(100).as_yoctonear());
+        contract.storage_deposit(None, None);
+        set_context(account_id, 0);
+    }
+
+    fn setup() -> BetSwapAI {
+        set_context(&owner(), 0);
+        let mut contract = BetSwapAI::new(owner());
+        deposit_storage(&mut contract, &owner());
+        contract.add_oracle(ORACLE_KEY.to_string());
+        contract.set_dispute_config(U64(1_000), 100);
+        contract.create_bet_event("event_a".to_string(), "A".to_string(), U64(1_000), None, None, None, None);
+        contract.create_bet_event("event_b".to_string(), "B".to_string(), U64(1_000), None, None, None, None);
+        contract
+    }
+
+    #[test]
+    fn bets_are_isolated_per_event() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        set_context(&alice(), 100);
+        contract.place_bet("event_a".to_string(), 1);
+        set_context(&alice(), 30);
+        contract.place_bet("event_b".to_string(), 0);
+
+        assert_eq!(contract.get_user_bet("event_a".to_string(), alice()).unwrap().stakes, vec![0, 100]);
+        assert_eq!(contract.get_user_bet("event_b".to_string(), alice()).unwrap().stakes, vec![30, 0]);
+    }
+
+    #[test]
+    fn repeated_bets_accumulate() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        for (outcome, amount) in [(1, 100), (1, 50), (0, 20)] {
+            set_context(&alice(), amount);
+            contract.place_bet("event_a".to_string(), outcome);
+        }
+        set_context(&alice(), 70);
+        contract.place_bet("event_b".to_string(), 1);
+
+        assert_eq!(contract.get_user_bet("event_a".to_string(), alice()).unwrap().stakes, vec![20, 150]);
+        assert_eq!(contract.get_user_bet("event_b".to_string(), alice()).unwrap().stakes, vec![0, 70]);
+
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert_eq!(event.total_bets, U128(170));
+        assert_eq!(event.pools, vec![U128(20), U128(150)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event already exists")]
+    fn event_ids_are_unique() {
+        let mut contract = setup();
+        contract.create_bet_event("event_a".to_string(), "A again".to_string(), U64(1_000), None, None, None, None);
+    }
+
+    /// Places a NEAR bet, registering `user` for storage first if needed
+    fn bet(contract: &mut BetSwapAI, user: &AccountId, event_id: &str, outcome: u32, amount: u128) {
+        if contract.storage_balance_of(user.clone()).is_none() {
+            deposit_storage(contract, user);
+        }
+        set_context(user, amount);
+        contract.place_bet(event_id.to_string(), outcome);
+    }
+
+    /// Places a cross-chain bet of `amount` on outcome 1 of `event_a` at `timestamp`
+    fn cross_chain_bet(contract: &mut BetSwapAI, user: &AccountId, amount: u128, timestamp: u64) -> String {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(user.clone()).attached_deposit(NearToken::from_yoctonear(amount)).block_timestamp(timestamp);
+        testing_env!(context.build());
+        contract.place_cross_chain_bet("event_a".to_string(), U128(amount), 1, "0xabc".to_string())
+    }
+
+    fn report(public_key: &str, outcome: u32, confidence: u64, signature: &str) -> String {
+        serde_json::json!({
+            "event_id": "event_a",
+            "outcome": outcome,
+            "confidence": confidence,
+            "timestamp": "2000",
+            "public_key": public_key,
+            "signature": signature,
+        })
+        .to_string()
+    }
+
+    /// The signed report of `public_key` for `outcome`
+    fn signed_report(public_key: &str, outcome: u32) -> String {
+        let (key, outcome, confidence, signature) = REPORTS.into_iter().find(|(key, reported, _, _)| *key == public_key && *reported == outcome).unwrap();
+        report(key, outcome, confidence, signature)
+    }
+
+    /// Resolves `event_a` to outcome 1 with a signed oracle report
+    fn resolve(contract: &mut BetSwapAI) {
+        set_time(&owner(), 2_000);
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, 1));
+    }
+
+    /// NEAR transferred to `account_id` by the receipts created so far
+    fn transfers_to(account_id: &AccountId) -> Vec<u128> {
+        near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id == *account_id)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                near_sdk::mock::MockAction::Transfer { deposit, .. } => Some(deposit.as_yoctonear()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn transfer_result(result: near_sdk::PromiseResult) {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(owner()).current_account_id(owner());
+        testing_env!(
+            context.build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result]
+        );
+    }
+
+    #[test]
+    fn owner_manages_accepted_tokens() {
+        let mut contract = setup();
+        contract.add_accepted_token(usdc());
+        assert_eq!(contract.get_accepted_tokens(), vec![usdc()]);
+        contract.remove_accepted_token(usdc());
+        assert!(contract.get_accepted_tokens().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Admin")]
+    fn only_owner_adds_tokens() {
+        let mut contract = setup();
+        set_context(&alice(), 0);
+        contract.add_accepted_token(usdc());
+    }
+
+    #[test]
+    fn create_categorical_event() {
+        let mut contract = setup();
+        let teams: Vec<String> = (1..=5).map(|i| format!("Team {}", i)).collect();
+        contract.create_bet_event("cup".to_string(), "Cup winner".to_string(), U64(1_000), None, Some(teams.clone()), None, None);
+
+        let event = contract.get_bet_event("cup".to_string()).unwrap();
+        assert_eq!(event.outcomes, teams);
+        assert_eq!(event.pools, vec![U128(0); 5]);
+        assert_eq!(contract.get_bet_event("event_a".to_string()).unwrap().outcomes, BINARY_OUTCOMES);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token not accepted")]
+    fn create_event_rejects_unknown_token() {
+        let mut contract = setup();
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), Some(usdc()), None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event not open for bets")]
+    fn place_bet_rejects_unknown_outcome() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 2, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event is staked in a NEP-141 token")]
+    fn place_bet_rejects_token_event() {
+        let mut contract = setup();
+        contract.add_accepted_token(usdc());
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), Some(usdc()), None, None, None);
+        bet(&mut contract, &alice(), "event_c", 1, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account not registered for storage")]
+    fn place_bet_requires_storage_deposit() {
+        let mut contract = setup();
+        set_context(&alice(), 100);
+        contract.place_bet("event_a".to_string(), 1);
+    }
+
+    #[test]
+    fn first_bet_on_event_is_charged() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        let deposited = contract.storage_balance_of(alice()).unwrap().available;
+        bet(&mut contract, &alice(), "event_a", 1, 100);
+        let charged = contract.storage_balance_of(alice()).unwrap().available;
+        assert!(charged.0 < deposited.0);
+
+        // Adding to an existing stake uses no new storage
+        bet(&mut contract, &alice(), "event_a", 0, 100);
+        assert_eq!(contract.storage_balance_of(alice()).unwrap().available, charged);
+    }
+
+    #[test]
+    fn bettor_unregisters_after_claiming() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 100);
+        resolve(&mut contract);
+        set_time(&alice(), 3_000);
+        contract.claim_winnings("event_a".to_string());
+
+        transfer_result(near_sdk::PromiseResult::Successful(vec![]));
+        assert!(contract.on_winnings_transferred("event_a".to_string(), alice()));
+        assert!(contract.get_user_bet("event_a".to_string(), alice()).is_none());
+
+        // The rewards entry of the bet is freed when the rewards are claimed
+        set_context(&alice(), 0);
+        contract.claim_rewards();
+        let balance = contract.storage_balance_of(alice()).unwrap();
+        assert_eq!(balance.available.0, balance.total.0 - StorageLedger::bounds().min.0);
+
+        set_context(&alice(), 1);
+        assert!(contract.storage_unregister(None));
+    }
+
+    #[test]
+    #[should_panic(expected = "No bet placed")]
+    fn paid_refund_cannot_be_claimed_again() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 100);
+        set_time(&owner(), 500);
+        contract.void_event("event_a".to_string());
+        set_time(&alice(), 600);
+        contract.claim_refund("event_a".to_string());
+
+        transfer_result(near_sdk::PromiseResult::Successful(vec![]));
+        assert!(contract.on_winnings_transferred("event_a".to_string(), alice()));
+        set_time(&alice(), 700);
+        contract.claim_refund("event_a".to_string());
+    }
+
+    #[test]
+    fn ft_on_transfer_records_bet_or_returns_tokens() {
+        let mut contract = setup();
+        contract.add_accepted_token(usdc());
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), Some(usdc()), None, None, None);
+        set_context(&usdc(), 0);
+        let msg = r#"{"event_id":"event_c","outcome":1}"#.to_string();
+
+        // Sender not registered for storage
+        assert!(matches!(contract.ft_on_transfer(alice(), U128(500), msg.clone()), PromiseOrValue::Value(U128(500))));
+        assert!(contract.get_user_bet("event_c".to_string(), alice()).is_none());
+
+        deposit_storage(&mut contract, &alice());
+        set_context(&usdc(), 0);
+        assert!(matches!(contract.ft_on_transfer(alice(), U128(500), msg), PromiseOrValue::Value(U128(0))));
+        assert_eq!(contract.get_user_bet("event_c".to_string(), alice()).unwrap().stakes, vec![0, 500]);
+
+        // Wrong stake token for the event
+        let msg = r#"{"event_id":"event_a","outcome":1}"#.to_string();
+        assert!(matches!(contract.ft_on_transfer(alice(), U128(500), msg), PromiseOrValue::Value(U128(500))));
+        assert!(contract.get_user_bet("event_a".to_string(), alice()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token not accepted")]
+    fn ft_on_transfer_rejects_unknown_token() {
+        let mut contract = setup();
+        set_context(&usdc(), 0);
+        contract.ft_on_transfer(alice(), U128(500), r#"{"event_id":"event_a","outcome":1}"#.to_string());
+    }
+
+    #[test]
+    fn place_cross_chain_bet_creates_intent() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        let bet_id = cross_chain_bet(&mut contract, &alice(), 1_000, 0);
+
+        let cross_chain_bet = contract.cross_chain_bets.get(&bet_id).unwrap();
+        assert_eq!(cross_chain_bet.user, alice());
+        assert_eq!(cross_chain_bet.outcome, 1);
+        let intent = contract.bet_intents.get(&format!("intent_{}", bet_id)).unwrap();
+        assert_eq!(intent.amount, 1_000);
+        assert_eq!(intent.target_chain, "ETH");
+        assert_eq!(contract.get_user_bet("event_a".to_string(), alice()).unwrap().stakes, vec![0, 1_000]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event not found")]
+    fn cross_chain_bet_requires_event() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        set_context(&alice(), 1_000);
+        contract.place_cross_chain_bet("missing".to_string(), U128(1_000), 1, "0xabc".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must match amount")]
+    fn cross_chain_bet_amount_must_be_attached() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        set_context(&alice(), 1);
+        contract.place_cross_chain_bet("event_a".to_string(), U128(1_000), 1, "0xabc".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Event not open for bets")]
+    fn cross_chain_bet_rejects_unknown_outcome() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        set_context(&alice(), 1_000);
+        contract.place_cross_chain_bet("event_a".to_string(), U128(1_000), 7, "0xabc".to_string());
+    }
+
+    #[test]
+    fn predict_outcome_resolves_event() {
+        let mut contract = setup();
+        resolve(&mut contract);
+
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert!(event.resolved);
+        assert_eq!(event.outcome, 1);
+        let prediction = contract.get_ai_prediction("event_a".to_string()).unwrap();
+        assert_eq!(prediction.predicted_outcome, 1);
+        assert_eq!(prediction.confidence, 90);
+        assert_eq!(prediction.oracle_data, serde_json::to_string(&[signed_report(ORACLE_KEY, 1)]).unwrap());
+    }
+
+    #[test]
+    fn resolves_to_reported_outcome() {
+        let mut contract = setup();
+        set_time(&owner(), 2_000);
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, 0));
+        assert_eq!(contract.get_bet_event("event_a".to_string()).unwrap().outcome, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid oracle signature")]
+    fn resolution_rejects_tampered_report() {
+        let mut contract = setup();
+        set_time(&owner(), 2_000);
+        contract.predict_outcome_with_ai("event_a".to_string(), report(ORACLE_KEY, 0, 90, REPORTS[0].3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid oracle signature")]
+    fn resolution_rejects_removed_oracle() {
+        let mut contract = setup();
+        contract.remove_oracle(ORACLE_KEY.to_string());
+        assert!(contract.get_oracles().is_empty());
+        resolve(&mut contract);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event not ended")]
+    fn resolution_rejected_before_end_time() {
+        let mut contract = setup();
+        set_time(&owner(), 999);
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Event not open for bets")]
+    fn bets_rejected_after_end_time() {
+        let mut contract = setup();
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(alice()).block_timestamp(1_000).attached_deposit(NearToken::from_yoctonear(100));
+        testing_env!(context.build());
+        contract.place_bet("event_a".to_string(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Admin")]
+    fn only_owner_adds_oracles() {
+        let mut contract = setup();
+        set_context(&alice(), 0);
+        contract.add_oracle(ORACLE_KEY.to_string());
+    }
+
+    #[test]
+    fn winners_claim_pro_rata() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        bet(&mut contract, &bob(), "event_a", 1, 100);
+        bet(&mut contract, &owner(), "event_a", 0, 200);
+        resolve(&mut contract);
+
+        set_time(&alice(), 3_000);
+        contract.claim_winnings("event_a".to_string());
+        // 300 + 300 * 200 / 400
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::WinningsClaimed { event_id: "event_a".to_string(), account_id: alice(), amount: U128(450) }.to_log()]);
+        assert!(contract.get_user_bet("event_a".to_string(), alice()).unwrap().claimed);
+    }
+
+    #[test]
+    fn near_scale_stakes_do_not_overflow() {
+        let mut contract = setup();
+        let one_near = NearToken::from_near(1).as_yoctonear();
+        bet(&mut contract, &alice(), "event_a", 1, 3 * one_near);
+        bet(&mut contract, &bob(), "event_a", 1, one_near);
+        bet(&mut contract, &owner(), "event_a", 0, 2 * one_near);
+        resolve(&mut contract);
+
+        set_time(&alice(), 3_000);
+        contract.claim_winnings("event_a".to_string());
+        // 3 NEAR + 3 NEAR * 2 NEAR / 4 NEAR
+        let payout = U128(3 * one_near + 3 * one_near / 2);
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::WinningsClaimed { event_id: "event_a".to_string(), account_id: alice(), amount: payout }.to_log()]);
+    }
+
+    #[test]
+    fn unbacked_outcome_refunds_stakes() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 0, 300);
+        resolve(&mut contract);
+
+        set_time(&alice(), 3_000);
+        contract.claim_refund("event_a".to_string());
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::RefundClaimed { event_id: "event_a".to_string(), account_id: alice(), amount: U128(300) }.to_log()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event not refundable")]
+    fn backed_outcome_is_not_refundable() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 0, 300);
+        bet(&mut contract, &bob(), "event_a", 1, 100);
+        resolve(&mut contract);
+
+        set_time(&alice(), 3_000);
+        contract.claim_refund("event_a".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Winnings already claimed")]
+    fn winnings_cannot_be_claimed_twice() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        resolve(&mut contract);
+
+        set_time(&alice(), 3_000);
+        contract.claim_winnings("event_a".to_string());
+        contract.claim_winnings("event_a".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "No winnings to claim")]
+    fn losers_cannot_claim() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 0, 300);
+        resolve(&mut contract);
+
+        set_time(&alice(), 3_000);
+        contract.claim_winnings("event_a".to_string());
+    }
+
+    #[test]
+    fn failed_winnings_transfer_reopens_claim() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        resolve(&mut contract);
+        set_time(&alice(), 3_000);
+        contract.claim_winnings("event_a".to_string());
+
+        transfer_result(near_sdk::PromiseResult::Failed);
+        assert!(!contract.on_winnings_transferred("event_a".to_string(), alice()));
+        assert!(!contract.get_user_bet("event_a".to_string(), alice()).unwrap().claimed);
+    }
+
+    #[test]
+    fn solver_flow_executes_signed_meta_order() {
+        let mut contract = setup();
+        let tee_config = TEEConfig {
+            enclave_id: "enclave".to_string(),
+            attestation: String::new(),
+            is_verified: true,
+            public_key: ED25519_KEY.to_string(),
+        };
+        contract.register_solver(bob(), U128(1), U128(1_000_000_000), U64(100), tee_config);
+        assert_eq!(contract.get_solver_reputation(bob()), U64(100));
+
+        contract.bet_intents.insert(
+            &"intent_1".to_string(),
+            &BetSwapIntent {
+                user: alice(),
+                event_id: "event_a".to_string(),
+                amount: 1_000_000,
+                outcome: 1,
+                target_chain: "ETH".to_string(),
+                deadline: 1_700_000_000_000_000_000,
+            },
+        );
+        let request_id = contract.request_bet_swap_quote("usdc".to_string(), "eth".to_string(), U128(1_000_000), U64(1_000));
+
+        deposit_storage(&mut contract, &bob());
+        let signature = ChainSignature {
+            signature: ED25519_SIGNATURE.to_string(),
+            public_key: ED25519_KEY.to_string(),
+            message: MESSAGE.to_string(),
+        };
+        let order_id = contract.generate_bet_swap_quote(request_id, U128(990_000), "intent_1".to_string(), signature);
+        assert_eq!(contract.meta_orders.get(&order_id).unwrap().event_id, "event_a");
+        assert_eq!(contract.get_solver_reputation(bob()), U64(110));
+
+        contract.execute_bet_meta_order(order_id.clone(), hex::encode([7u8; 32]));
+        assert!(contract.meta_orders.get(&order_id).unwrap().is_executed);
+        assert_eq!(contract.get_solver_reputation(bob()), U64(130));
+    }
+
+    /// Registers bob with `ED25519_KEY` and returns the id of his signed meta-order
+    fn setup_bet_meta_order() -> (BetSwapAI, String) {
+        let mut contract = setup();
+        let tee_config = TEEConfig {
+            enclave_id: "enclave".to_string(),
+            attestation: String::new(),
+            is_verified: true,
+            public_key: ED25519_KEY.to_string(),
+        };
+        contract.register_solver(bob(), U128(1), U128(1_000_000_000), U64(100), tee_config.clone());
+        contract.register_solver(alice(), U128(1), U128(1_000_000_000), U64(100), tee_config);
+        contract.bet_intents.insert(
+            &"intent_1".to_string(),
+            &BetSwapIntent {
+                user: alice(),
+                event_id: "event_a".to_string(),
+                amount: 1_000_000,
+                outcome: 1,
+                target_chain: "ETH".to_string(),
+                deadline: 1_700_000_000_000_000_000,
+            },
+        );
+        let request_id = contract.request_bet_swap_quote("usdc".to_string(), "eth".to_string(), U128(1_000_000), U64(1_000));
+
+        deposit_storage(&mut contract, &bob());
+        let signature = ChainSignature {
+            signature: ED25519_SIGNATURE.to_string(),
+            public_key: ED25519_KEY.to_string(),
+            message: MESSAGE.to_string(),
+        };
+        let order_id = contract.generate_bet_swap_quote(request_id, U128(990_000), "intent_1".to_string(), signature);
+        (contract, order_id)
+    }
+
+    #[test]
+    fn executed_bet_meta_order_removal_credits_solver() {
+        let (mut contract, order_id) = setup_bet_meta_order();
+        contract.execute_bet_meta_order(order_id.clone(), hex::encode([7u8; 32]));
+
+        set_context(&alice(), 0);
+        contract.remove_bet_meta_order(order_id.clone());
+        assert!(contract.meta_orders.get(&order_id).is_none());
+        let balance = contract.storage_balance_of(bob()).unwrap();
+        assert_eq!(balance.available.0, balance.total.0 - StorageLedger::bounds().min.0);
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::MetaOrderRemoved { order_id }.to_log()]);
+    }
+
+    /// Alice places a cross-chain bet at t=100 and bob quotes its intent at `timestamp`.
+    /// Returns the id of bob's meta-order.
+    fn quote_cross_chain_bet(timestamp: u64) -> (BetSwapAI, String) {
+        let mut contract = setup();
+        let tee_config = TEEConfig {
+            enclave_id: "enclave".to_string(),
+            attestation: String::new(),
+            is_verified: true,
+            public_key: ED25519_KEY.to_string(),
+        };
+        contract.register_solver(bob(), U128(1), U128(1_000_000_000), U64(100), tee_config);
+        let request_id = contract.request_bet_swap_quote("usdc".to_string(), "eth".to_string(), U128(1_000), U64(u64::MAX));
+        deposit_storage(&mut contract, &alice());
+        deposit_storage(&mut contract, &bob());
+        let bet_id = cross_chain_bet(&mut contract, &alice(), 1_000, 100);
+
+        set_time(&bob(), timestamp);
+        let signature = ChainSignature {
+            signature: ED25519_SIGNATURE.to_string(),
+            public_key: ED25519_KEY.to_string(),
+            message: MESSAGE.to_string(),
+        };
+        let order_id = contract.generate_bet_swap_quote(request_id, U128(990), format!("intent_{}", bet_id), signature);
+        (contract, order_id)
+    }
+
+    #[test]
+    fn cross_chain_bet_intent_is_open_for_an_hour() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        let bet_id = cross_chain_bet(&mut contract, &alice(), 1_000, 100);
+        assert_eq!(contract.bet_intents.get(&format!("intent_{}", bet_id)).unwrap().deadline, 100 + 3_600_000_000_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Order not finished")]
+    fn pending_bet_meta_order_cannot_be_removed() {
+        let (mut contract, order_id) = quote_cross_chain_bet(5_000);
+        set_time(&carol(), 5_001);
+        contract.remove_bet_meta_order(order_id);
+    }
+
+    #[test]
+    fn expired_bet_meta_order_removal_credits_solver() {
+        let (mut contract, order_id) = quote_cross_chain_bet(5_000);
+        set_time(&carol(), 100 + 3_600_000_000_001);
+        contract.remove_bet_meta_order(order_id.clone());
+        assert!(contract.meta_orders.get(&order_id).is_none());
+        let balance = contract.storage_balance_of(bob()).unwrap();
+        assert_eq!(balance.available.0, balance.total.0 - StorageLedger::bounds().min.0);
+    }
+
+    #[test]
+    fn expired_bet_swap_quote_removal_credits_requester() {
+        let mut contract = setup();
+        let available = contract.storage_balance_of(owner()).unwrap().available;
+        let request_id = contract.request_bet_swap_quote("usdc".to_string(), "eth".to_string(), U128(1_000_000), U64(1_000));
+        assert!(contract.storage_balance_of(owner()).unwrap().available.0 < available.0);
+
+        set_time(&alice(), 1_001);
+        contract.remove_expired_bet_swap_quote(request_id.clone());
+        assert!(contract.quote_requests.get(&request_id).is_none());
+        assert_eq!(contract.storage_balance_of(owner()).unwrap().available, available);
+    }
+
+    #[test]
+    #[should_panic(expected = "Quote not expired")]
+    fn open_bet_swap_quote_cannot_be_removed() {
+        let mut contract = setup();
+        let request_id = contract.request_bet_swap_quote("usdc".to_string(), "eth".to_string(), U128(1_000_000), U64(1_000));
+        set_time(&alice(), 1_000);
+        contract.remove_expired_bet_swap_quote(request_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Order already executed")]
+    fn bet_meta_order_executes_once() {
+        let (mut contract, order_id) = setup_bet_meta_order();
+        contract.execute_bet_meta_order(order_id.clone(), hex::encode([7u8; 32]));
+        contract.execute_bet_meta_order(order_id, hex::encode([7u8; 32]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only quoting solver can execute")]
+    fn other_solvers_cannot_execute_bet_meta_order() {
+        let (mut contract, order_id) = setup_bet_meta_order();
+        set_context(&alice(), 0);
+        contract.execute_bet_meta_order(order_id, hex::encode([7u8; 32]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Signature key does not match solver key")]
+    fn bet_quote_requires_registered_solver_key() {
+        let mut contract = setup();
+        let tee_config = TEEConfig {
+            enclave_id: "enclave".to_string(),
+            attestation: String::new(),
+            is_verified: true,
+            public_key: ORACLE_KEY.to_string(),
+        };
+        contract.register_solver(bob(), U128(1), U128(1_000_000_000), U64(100), tee_config);
+        contract.request_bet_swap_quote("usdc".to_string(), "eth".to_string(), U128(1_000_000), U64(1_000));
+
+        deposit_storage(&mut contract, &bob());
+        let signature = ChainSignature {
+            signature: ED25519_SIGNATURE.to_string(),
+            public_key: ED25519_KEY.to_string(),
+            message: MESSAGE.to_string(),
+        };
+        contract.generate_bet_swap_quote("quote_1".to_string(), U128(990_000), "intent_1".to_string(), signature);
+    }
+
+    #[test]
+    #[should_panic(expected = "Quote request not found")]
+    fn generate_quote_requires_request() {
+        let mut contract = setup();
+        let tee_config = TEEConfig {
+            enclave_id: "enclave".to_string(),
+            attestation: String::new(),
+            is_verified: true,
+            public_key: ED25519_KEY.to_string(),
+        };
+        contract.register_solver(bob(), U128(1), U128(1_000_000_000), U64(100), tee_config);
+        set_context(&bob(), 0);
+        let signature = ChainSignature {
+            signature: String::new(),
+            public_key: ED25519_KEY.to_string(),
+            message: String::new(),
+        };
+        contract.generate_bet_swap_quote("quote_1".to_string(), U128(1), "intent_1".to_string(), signature);
+    }
+
+    #[test]
+    fn claim_rewards_resets_counter_once() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 1_000);
+        assert_eq!(contract.get_user_rewards(alice()), U128(10));
+
+        set_context(&alice(), 0);
+        assert_eq!(contract.claim_rewards(), U128(10));
+        assert_eq!(contract.get_user_rewards(alice()), U128(0));
+    }
+
+    /// Event `event_a` resolved by 2 of `ORACLE_KEY`, `ORACLE_KEY_2`, `ORACLE_KEY_3`
+    fn setup_quorum(dispute_margin: u32) -> BetSwapAI {
+        set_context(&owner(), 0);
+        let mut contract = BetSwapAI::new(owner());
+        deposit_storage(&mut contract, &owner());
+        let oracles: Vec<String> = [ORACLE_KEY, ORACLE_KEY_2, ORACLE_KEY_3].iter().map(|key| key.to_string()).collect();
+        for oracle in &oracles {
+            contract.add_oracle(oracle.clone());
+        }
+        let quorum = OracleQuorum {
+            oracles,
+            threshold: 2,
+            dispute_margin,
+        };
+        contract.create_bet_event("event_a".to_string(), "A".to_string(), U64(1_000), None, None, Some(quorum), None);
+        set_time(&owner(), 2_000);
+        contract
+    }
+
+    #[test]
+    fn quorum_resolves_with_median_confidence() {
+        let mut contract = setup_quorum(1);
+        assert!(contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, 1)).is_none());
+        assert!(contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY_2, 0)).is_none());
+        assert!(!contract.get_bet_event("event_a".to_string()).unwrap().resolved);
+
+        let prediction = contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY_3, 1)).unwrap();
+        assert_eq!(prediction.predicted_outcome, 1);
+        // Median of 90 and 50
+        assert_eq!(prediction.confidence, 70);
+
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert!(event.resolved);
+        assert_eq!(event.outcome, 1);
+
+        let submissions = contract.get_oracle_submissions("event_a".to_string());
+        assert_eq!(submissions.len(), 3);
+        assert_eq!(submissions[1].public_key, ORACLE_KEY_2);
+        assert_eq!(submissions[1].outcome, 0);
+        assert_eq!(submissions[1].confidence, 60);
+    }
+
+    #[test]
+    fn disagreement_beyond_margin_disputes_event() {
+        let mut contract = setup_quorum(0);
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, 1));
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY_2, 0));
+
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert!(event.disputed);
+        assert!(!event.resolved);
+    }
+
+    #[test]
+    #[should_panic(expected = "Oracle already reported")]
+    fn oracle_reports_once() {
+        let mut contract = setup_quorum(1);
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, 1));
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Oracle not in event quorum")]
+    fn quorum_rejects_other_oracles() {
+        let mut contract = setup();
+        contract.add_oracle(ORACLE_KEY_2.to_string());
+        let quorum = OracleQuorum {
+            oracles: vec![ORACLE_KEY_2.to_string()],
+            threshold: 1,
+            dispute_margin: 0,
+        };
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), None, None, Some(quorum), None);
+        set_time(&owner(), 2_000);
+        let report = signed_report(ORACLE_KEY, 1).replace("event_a", "event_c");
+        contract.predict_outcome_with_ai("event_c".to_string(), report);
+    }
+
+    #[test]
+    #[should_panic(expected = "Dispute margin must be below threshold")]
+    fn quorum_margin_must_be_below_threshold() {
+        let mut contract = setup();
+        let quorum = OracleQuorum {
+            oracles: vec![ORACLE_KEY.to_string()],
+            threshold: 1,
+            dispute_margin: 1,
+        };
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), None, None, Some(quorum), None);
+    }
+
+    #[test]
+    fn tie_with_void_waits_for_the_deciding_report() {
+        let mut contract = setup_quorum(1);
+        assert!(contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, VOID_OUTCOME)).is_none());
+        assert!(contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY_2, 1)).is_none());
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert!(!event.disputed);
+        assert_ne!(event.resolution, "void");
+
+        let prediction = contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY_3, 1)).unwrap();
+        assert_eq!(prediction.predicted_outcome, 1);
+        assert!(contract.get_bet_event("event_a".to_string()).unwrap().resolved);
+    }
+
+    #[test]
+    #[should_panic(expected = "Threshold exceeds oracle count")]
+    fn quorum_threshold_must_be_reachable() {
+        let mut contract = setup();
+        let quorum = OracleQuorum {
+            oracles: vec![ORACLE_KEY.to_string()],
+            threshold: 2,
+            dispute_margin: 0,
+        };
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), None, None, Some(quorum), None);
+    }
+
+    /// `event_a` with 300 on outcome 1 and 200 on outcome 0, resolved to 1 at 2000
+    /// and challenged by bob for outcome 0
+    fn setup_challenge() -> BetSwapAI {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        bet(&mut contract, &owner(), "event_a", 0, 200);
+        resolve(&mut contract);
+
+        assert_eq!(contract.get_challenge_bond("event_a".to_string()), U128(5));
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(bob()).block_timestamp(2_500).attached_deposit(NearToken::from_yoctonear(5));
+        testing_env!(context.build());
+        contract.challenge_outcome("event_a".to_string(), 0);
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "Resolution still open to challenges")]
+    fn claims_locked_during_dispute_window() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        resolve(&mut contract);
+        set_time(&alice(), 2_999);
+        contract.claim_winnings("event_a".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Resolution still open to challenges")]
+    fn claims_locked_while_challenge_pending() {
+        let mut contract = setup_challenge();
+        set_time(&alice(), 5_000);
+        contract.claim_winnings("event_a".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot challenge event")]
+    fn challenge_requires_exact_bond() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        resolve(&mut contract);
+        set_context(&bob(), 4);
+        contract.challenge_outcome("event_a".to_string(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot challenge event")]
+    fn challenge_rejected_after_window() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        resolve(&mut contract);
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(bob()).block_timestamp(3_000).attached_deposit(NearToken::from_yoctonear(3));
+        testing_env!(context.build());
+        contract.challenge_outcome("event_a".to_string(), 0);
+    }
+
+    #[test]
+    fn upheld_challenge_switches_outcome_and_rewards_challenger() {
+        let mut contract = setup_challenge();
+        set_context(&owner(), 0);
+        let arbitrator: AccountId = "arbitrator.testnet".parse().unwrap();
+        contract.set_arbitrator(Some(arbitrator.clone()));
+        assert_eq!(contract.get_arbitrator(), Some(arbitrator.clone()));
+        set_time(&arbitrator, 2_600);
+        contract.rule_on_challenge("event_a".to_string(), true);
+
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert_eq!(event.outcome, 0);
+        assert!(event.challenge.is_none());
+        // Bond of 5 returned plus a reward of 5 taken from the losing stake
+        assert_eq!(event.total_bets, U128(495));
+
+        set_time(&owner(), 2_600);
+        contract.claim_winnings("event_a".to_string());
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::WinningsClaimed { event_id: "event_a".to_string(), account_id: owner(), amount: U128(495) }.to_log()]);
+    }
+
+    #[test]
+    fn failed_challenge_payout_can_be_retried() {
+        let mut contract = setup_challenge();
+        set_time(&owner(), 2_600);
+        contract.rule_on_challenge("event_a".to_string(), true);
+        assert_eq!(transfers_to(&bob()), vec![10]);
+
+        transfer_result(near_sdk::PromiseResult::Failed);
+        assert!(!contract.on_challenge_payout_transferred("event_a".to_string(), bob(), U128(10)));
+        assert_eq!(contract.unpaid_challenges.get(&"event_a".to_string()), Some((bob(), 10)));
+
+        // Anyone can retry, the payout still goes to the challenger
+        set_time(&alice(), 2_700);
+        contract.retry_challenge_payout("event_a".to_string());
+        assert!(contract.unpaid_challenges.get(&"event_a".to_string()).is_none());
+        assert_eq!(transfers_to(&bob()), vec![10]);
+    }
+
+    #[test]
+    #[should_panic(expected = "No failed challenge payout")]
+    fn paid_challenge_cannot_be_retried() {
+        let mut contract = setup_challenge();
+        set_time(&owner(), 2_600);
+        contract.rule_on_challenge("event_a".to_string(), true);
+        transfer_result(near_sdk::PromiseResult::Successful(vec![]));
+        assert!(contract.on_challenge_payout_transferred("event_a".to_string(), bob(), U128(10)));
+        contract.retry_challenge_payout("event_a".to_string());
+    }
+
+    #[test]
+    fn upheld_challenge_on_unbacked_outcome_refunds_stakes() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        resolve(&mut contract);
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(bob()).block_timestamp(2_500).attached_deposit(NearToken::from_yoctonear(3));
+        testing_env!(context.build());
+        contract.challenge_outcome("event_a".to_string(), 0);
+
+        set_time(&owner(), 2_600);
+        contract.rule_on_challenge("event_a".to_string(), true);
+        // Only the bond is returned, no reward is taken from stakes that are refunded
+        assert_eq!(transfers_to(&bob()), vec![3]);
+        assert_eq!(contract.get_bet_event("event_a".to_string()).unwrap().total_bets, U128(300));
+
+        set_time(&alice(), 2_700);
+        contract.claim_refund("event_a".to_string());
+        assert_eq!(transfers_to(&alice()), vec![300]);
+    }
+
+    #[test]
+    fn rejected_challenge_bond_goes_to_pool() {
+        let mut contract = setup_challenge();
+        set_time(&owner(), 2_600);
+        contract.rule_on_challenge("event_a".to_string(), false);
+
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert_eq!(event.outcome, 1);
+        assert_eq!(event.total_bets, U128(505));
+
+        set_time(&alice(), 2_600);
+        contract.claim_winnings("event_a".to_string());
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::WinningsClaimed { event_id: "event_a".to_string(), account_id: alice(), amount: U128(505) }.to_log()]);
+    }
+
+    #[test]
+    fn rejected_challenge_on_unbacked_outcome_returns_bond() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 0, 300);
+        resolve(&mut contract);
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(bob()).block_timestamp(2_500).attached_deposit(NearToken::from_yoctonear(3));
+        testing_env!(context.build());
+        contract.challenge_outcome("event_a".to_string(), 0);
+
+        set_time(&owner(), 2_600);
+        contract.rule_on_challenge("event_a".to_string(), false);
+        assert_eq!(transfers_to(&bob()), vec![3]);
+        assert_eq!(contract.get_bet_event("event_a".to_string()).unwrap().total_bets, U128(300));
+
+        set_time(&alice(), 2_700);
+        contract.claim_refund("event_a".to_string());
+        assert_eq!(transfers_to(&alice()), vec![300]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only admin or arbitrator can rule")]
+    fn only_arbitrator_rules() {
+        let mut contract = setup_challenge();
+        set_time(&alice(), 2_600);
+        contract.rule_on_challenge("event_a".to_string(), false);
+    }
+
+    #[test]
+    fn token_challenge_through_ft_on_transfer() {
+        let mut contract = setup();
+        contract.add_accepted_token(usdc());
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), Some(usdc()), None, None, None);
+        deposit_storage(&mut contract, &alice());
+        set_context(&usdc(), 0);
+        contract.ft_on_transfer(alice(), U128(1_000), r#"{"event_id":"event_c","outcome":1}"#.to_string());
+
+        // Test reports are signed for `event_a`, so resolve `event_c` directly
+        let mut event = contract.bet_events.get(&"event_c".to_string()).unwrap();
+        event.resolved = true;
+        event.outcome = 1;
+        event.dispute_end = 3_000;
+        contract.bet_events.insert(&"event_c".to_string(), &event);
+
+        set_time(&usdc(), 2_500);
+        let msg = r#"{"event_id":"event_c","outcome":0,"challenge":true}"#.to_string();
+        assert!(matches!(contract.ft_on_transfer(bob(), U128(9), msg.clone()), PromiseOrValue::Value(U128(9))));
+        assert!(matches!(contract.ft_on_transfer(bob(), U128(10), msg), PromiseOrValue::Value(U128(0))));
+        assert_eq!(contract.get_bet_event("event_c".to_string()).unwrap().challenge.unwrap().challenger, bob());
+    }
+
+    /// `event_a` requiring `min_confidence`, with 300 on outcome 1 and 200 on outcome 0.
+    /// Test reports are signed for `event_a`, so the threshold is set directly.
+    fn setup_gated(min_confidence: u64) -> BetSwapAI {
+        let mut contract = setup();
+        let mut event = contract.bet_events.get(&"event_a".to_string()).unwrap();
+        event.min_confidence = min_confidence;
+        contract.bet_events.insert(&"event_a".to_string(), &event);
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        bet(&mut contract, &bob(), "event_a", 0, 200);
+        contract
+    }
+
+    #[test]
+    fn confident_prediction_auto_resolves() {
+        let mut contract = setup_gated(90);
+        resolve(&mut contract);
+
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert!(event.resolved);
+        assert_eq!(event.resolution, "auto");
+        assert!(!contract.get_ai_prediction("event_a".to_string()).unwrap().advisory);
+    }
+
+    #[test]
+    fn low_confidence_prediction_needs_review() {
+        let mut contract = setup_gated(95);
+        resolve(&mut contract);
+
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert!(!event.resolved);
+        assert_eq!(event.resolution, "needs_review");
+        let prediction = contract.get_ai_prediction("event_a".to_string()).unwrap();
+        assert!(prediction.advisory);
+        assert_eq!(prediction.predicted_outcome, 1);
+
+        // The resolver overrides the advisory outcome, and claims open immediately
+        contract.resolve_event("event_a".to_string(), 0);
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert!(event.resolved);
+        assert_eq!(event.outcome, 0);
+        assert_eq!(event.resolution, "manual");
+        set_time(&bob(), 2_000);
+        contract.claim_winnings("event_a".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Event needs review")]
+    fn review_blocks_further_reports() {
+        let mut contract = setup_gated(95);
+        resolve(&mut contract);
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY_2, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only admin or arbitrator can resolve")]
+    fn only_resolver_settles_review() {
+        let mut contract = setup_gated(95);
+        resolve(&mut contract);
+        set_time(&alice(), 2_000);
+        contract.resolve_event("event_a".to_string(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event does not need review")]
+    fn resolve_event_requires_review() {
+        let mut contract = setup();
+        set_time(&owner(), 2_000);
+        contract.resolve_event("event_a".to_string(), 1);
+    }
+
+    #[test]
+    fn arbitrator_settles_disputed_event() {
+        let mut contract = setup_quorum(0);
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, 1));
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY_2, 0));
+        let arbitrator: AccountId = "arbitrator.testnet".parse().unwrap();
+        contract.set_arbitrator(Some(arbitrator.clone()));
+
+        set_time(&arbitrator, 2_500);
+        contract.resolve_event("event_a".to_string(), 1);
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert!(event.resolved);
+        assert_eq!(event.resolution, "manual");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid minimum confidence")]
+    fn min_confidence_is_a_percentage() {
+        let mut contract = setup();
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), None, None, None, Some(101));
+    }
+
+    #[test]
+    fn void_event_refunds_exact_stakes() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        bet(&mut contract, &alice(), "event_a", 0, 100);
+        bet(&mut contract, &bob(), "event_a", 0, 200);
+
+        set_time(&owner(), 500);
+        contract.void_event("event_a".to_string());
+        assert_eq!(contract.get_bet_event("event_a".to_string()).unwrap().resolution, "void");
+
+        set_time(&alice(), 600);
+        contract.claim_refund("event_a".to_string());
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::RefundClaimed { event_id: "event_a".to_string(), account_id: alice(), amount: U128(400) }.to_log()]);
+        assert!(contract.get_user_bet("event_a".to_string(), alice()).unwrap().claimed);
+    }
+
+    #[test]
+    fn void_event_leaves_cross_chain_bets_for_removal() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        let bet_id = cross_chain_bet(&mut contract, &alice(), 1_000, 500);
+
+        set_time(&owner(), 600);
+        contract.void_event("event_a".to_string());
+        assert!(contract.bet_intents.get(&format!("intent_{}", bet_id)).is_some());
+
+        set_time(&bob(), 700);
+        contract.remove_cross_chain_bet(bet_id.clone());
+        assert!(contract.cross_chain_bets.get(&bet_id).is_none());
+        assert!(contract.bet_intents.get(&format!("intent_{}", bet_id)).is_none());
+    }
+
+    #[test]
+    fn void_event_refunds_cross_chain_stakes() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        cross_chain_bet(&mut contract, &alice(), 1_000, 500);
+
+        set_time(&owner(), 600);
+        contract.void_event("event_a".to_string());
+        set_time(&alice(), 700);
+        contract.claim_refund("event_a".to_string());
+        assert!(contract.get_user_bet("event_a".to_string(), alice()).unwrap().claimed);
+        assert_eq!(transfers_to(&alice()), vec![1_000]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bet not settled")]
+    fn voiding_another_event_does_not_settle_cross_chain_bets() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        let bet_id = cross_chain_bet(&mut contract, &alice(), 1_000, 500);
+
+        set_time(&owner(), 600);
+        contract.void_event("event_b".to_string());
+        contract.remove_cross_chain_bet(bet_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account not registered for storage")]
+    fn create_bet_event_requires_storage_deposit() {
+        let mut contract = setup();
+        contract.grant_role(alice(), Role::MarketCreator);
+        set_context(&alice(), 0);
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), None, None, None, None);
+    }
+
+    #[test]
+    fn settled_cross_chain_bet_removal_credits_storage() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        // The stake stays in `user_bets` for the refund, so only the cross-chain records are credited back
+        bet(&mut contract, &alice(), "event_a", 1, 100);
+        let deposited = contract.storage_balance_of(alice()).unwrap().available;
+        let bet_id = cross_chain_bet(&mut contract, &alice(), 1_000, 500);
+        assert!(contract.storage_balance_of(alice()).unwrap().available.0 < deposited.0);
+
+        set_time(&owner(), 600);
+        contract.void_event("event_a".to_string());
+        set_time(&bob(), 700);
+        contract.remove_cross_chain_bet(bet_id.clone());
+        assert!(contract.cross_chain_bets.get(&bet_id).is_none());
+        assert_eq!(contract.storage_balance_of(alice()).unwrap().available, deposited);
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::CrossChainBetRemoved { bet_id }.to_log()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bet not settled")]
+    fn pending_cross_chain_bet_cannot_be_removed() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &alice());
+        let bet_id = cross_chain_bet(&mut contract, &alice(), 1_000, 0);
+        resolve(&mut contract);
+        contract.remove_cross_chain_bet(bet_id);
+    }
+
+    #[test]
+    fn oracle_quorum_voids_event() {
+        let mut contract = setup_quorum(0);
+        assert!(contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, VOID_OUTCOME)).is_none());
+        assert!(contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY_2, VOID_OUTCOME)).is_none());
+
+        let event = contract.get_bet_event("event_a".to_string()).unwrap();
+        assert_eq!(event.resolution, "void");
+        assert!(!event.resolved);
+        assert!(contract.get_ai_prediction("event_a".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Event not open for bets")]
+    fn void_event_closes_betting() {
+        let mut contract = setup();
+        set_time(&owner(), 500);
+        contract.void_event("event_a".to_string());
+        bet(&mut contract, &alice(), "event_a", 1, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Admin")]
+    fn only_owner_voids_events() {
+        let mut contract = setup();
+        set_context(&alice(), 0);
+        contract.void_event("event_a".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Event already resolved")]
+    fn resolved_event_cannot_be_voided() {
+        let mut contract = setup();
+        resolve(&mut contract);
+        contract.void_event("event_a".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Event not resolved")]
+    fn void_event_pays_no_winnings() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        set_context(&owner(), 0);
+        contract.void_event("event_a".to_string());
+        set_time(&alice(), 3_000);
+        contract.claim_winnings("event_a".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Refund already claimed")]
+    fn refund_cannot_be_claimed_twice() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        set_context(&owner(), 0);
+        contract.void_event("event_a".to_string());
+        set_context(&alice(), 0);
+        contract.claim_refund("event_a".to_string());
+        contract.claim_refund("event_a".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Solver not registered")]
+    fn generate_quote_requires_registered_solver() {
+        let mut contract = setup();
+        let signature = ChainSignature {
+            signature: String::new(),
+            public_key: ED25519_KEY.to_string(),
+            message: String::new(),
+        };
+        set_context(&bob(), 0);
+        contract.generate_bet_swap_quote("quote_1".to_string(), U128(1), "intent_1".to_string(), signature);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role MarketCreator")]
+    fn only_market_creators_create_events() {
+        let mut contract = setup();
+        set_context(&alice(), 0);
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Oracle")]
+    fn only_oracles_submit_reports() {
+        let mut contract = setup();
+        set_time(&alice(), 2_000);
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role SolverManager")]
+    fn only_solver_managers_register_solvers() {
+        let mut contract = setup();
+        let tee_config = TEEConfig {
+            enclave_id: "enclave".to_string(),
+            attestation: String::new(),
+            is_verified: true,
+            public_key: ED25519_KEY.to_string(),
+        };
+        set_context(&bob(), 0);
+        contract.register_solver(bob(), U128(1), U128(1_000_000_000), U64(100), tee_config);
+    }
+
+    #[test]
+    fn granted_roles_unlock_methods() {
+        let mut contract = setup();
+        contract.grant_role(alice(), Role::MarketCreator);
+        contract.grant_role(bob(), Role::Oracle);
+        assert_eq!(contract.get_roles(alice()), vec![Role::MarketCreator]);
+        assert!(contract.has_role(owner(), Role::Pauser));
+
+        deposit_storage(&mut contract, &alice());
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), None, None, None, None);
+        set_time(&bob(), 2_000);
+        contract.predict_outcome_with_ai("event_a".to_string(), signed_report(ORACLE_KEY, 1));
+        assert!(contract.get_bet_event("event_a".to_string()).unwrap().resolved);
+
+        set_context(&owner(), 0);
+        contract.revoke_role(bob(), Role::Oracle);
+        assert!(!contract.has_role(bob(), Role::Oracle));
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Admin")]
+    fn ownership_transfer_moves_admin_rights() {
+        let mut contract = setup();
+        contract.transfer_ownership(alice());
+        assert_eq!(contract.get_owner(), alice());
+
+        set_context(&alice(), 0);
+        contract.add_oracle(ORACLE_KEY_2.to_string());
+        set_context(&owner(), 0);
+        contract.add_oracle(ORACLE_KEY_3.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Betting paused")]
+    fn paused_betting_rejects_bets() {
+        let mut contract = setup();
+        contract.pause(Some(Subsystem::Betting));
+        assert!(contract.get_pause_state().subsystems.contains(&Subsystem::Betting));
+        bet(&mut contract, &alice(), "event_a", 1, 100);
+    }
+
+    #[test]
+    fn paused_betting_returns_tokens() {
+        let mut contract = setup();
+        contract.add_accepted_token(usdc());
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(1_000), Some(usdc()), None, None, None);
+        contract.pause(None);
+
+        set_context(&usdc(), 0);
+        let msg = r#"{"event_id":"event_c","outcome":1}"#.to_string();
+        assert!(matches!(contract.ft_on_transfer(alice(), U128(1_000), msg), PromiseOrValue::Value(U128(1_000))));
+    }
+
+    #[test]
+    #[should_panic(expected = "Claims paused")]
+    fn paused_claims_reject_winnings() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        resolve(&mut contract);
+        contract.pause(Some(Subsystem::Claims));
+        set_time(&alice(), 3_000);
+        contract.claim_winnings("event_a".to_string());
+    }
+
+    #[test]
+    fn refunds_stay_open_while_paused() {
+        let mut contract = setup();
+        bet(&mut contract, &alice(), "event_a", 1, 300);
+        set_context(&owner(), 0);
+        contract.void_event("event_a".to_string());
+        contract.pause(None);
+
+        set_context(&alice(), 0);
+        contract.claim_refund("event_a".to_string());
+        assert!(contract.get_user_bet("event_a".to_string(), alice()).unwrap().claimed);
+    }
+
+    #[test]
+    fn pauser_role_controls_switches() {
+        let mut contract = setup();
+        contract.grant_role(bob(), Role::Pauser);
+        set_context(&bob(), 0);
+        contract.pause(Some(Subsystem::Quoting));
+        contract.pause(None);
+        contract.unpause(None);
+
+        let state = contract.get_pause_state();
+        assert!(!state.global);
+        assert_eq!(state.subsystems, vec![Subsystem::Quoting]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Pauser")]
+    fn only_pausers_pause() {
+        let mut contract = setup();
+        set_context(&alice(), 0);
+        contract.pause(None);
+    }
+
+    #[test]
+    fn bet_events_paginate_by_status() {
+        let mut contract = setup();
+        contract.create_bet_event("event_c".to_string(), "C".to_string(), U64(5_000), None, None, None, None);
+        contract.void_event("event_b".to_string());
+        resolve(&mut contract);
+
+        let ids = |events: Vec<(String, BetEventView)>| events.into_iter().map(|(event_id, _)| event_id).collect::<Vec<_>>();
+        assert_eq!(ids(contract.get_bet_events(Some("resolved".to_string()), None, None)), vec!["event_a"]);
+        assert_eq!(ids(contract.get_bet_events(Some("void".to_string()), None, None)), vec!["event_b"]);
+        assert_eq!(ids(contract.get_bet_events(Some("open".to_string()), None, None)), vec!["event_c"]);
+        assert_eq!(ids(contract.get_bet_events(None, Some(U64(1)), Some(U64(1)))), vec!["event_b"]);
+        assert_eq!(contract.get_bet_event("event_c".to_string()).unwrap().status, "open");
+    }
+}
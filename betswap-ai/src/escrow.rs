@@ -0,0 +1,628 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{ext_contract, AccountId};
+#[cfg(feature = "escrow")]
+use near_sdk::{
+    collections::UnorderedMap, env, near_bindgen, Gas, NearToken, PanicOnDefault, Promise,
+    PromiseOrValue, PromiseResult,
+};
+
+#[cfg(feature = "escrow")]
+use crate::events::Event;
+#[cfg(feature = "escrow")]
+use crate::storage::{StorageBalance, StorageBalanceBounds, StorageLedger};
+
+/// Gas for the NEP-141 `ft_transfer` call
+#[cfg(feature = "escrow")]
+const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+
+/// Gas reserved for the transfer callback
+#[cfg(feature = "escrow")]
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas::from_tgas(10);
+
+/// Nanoseconds per second, escrow timelocks are kept in seconds like `HTLC.sol`
+#[cfg(feature = "escrow")]
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+#[ext_contract(ext_ft)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+/// Lock parameters sent as the `msg` of `ft_transfer_call`
+///
+/// Funds can be refunded `timelock` seconds after the lock, as in `HTLC.sol`. The
+/// resolver alone can withdraw for the first `exclusive_period` seconds (the whole
+/// `timelock` by default); after that anyone can trigger the withdrawal to it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LockMsg {
+    pub hashlock: String,
+    pub timelock: u64,
+    pub exclusive_period: Option<u64>,
+    pub dest_chain: String,
+    pub dest_user: String,
+    pub min_return: U128,
+    pub output_token: String,
+    pub resolver_id: Option<AccountId>,
+}
+
+/// HTLC escrow holding NEP-141 tokens (or native NEAR when `token` is `None`)
+/// until the secret is revealed or the recovery time passes
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+pub struct Escrow {
+    pub sender: AccountId,
+    pub token: Option<AccountId>,
+    pub amount: U128,
+    pub hashlock: Vec<u8>,
+    pub timelock_exclusive: u64,
+    pub timelock_recovery: u64,
+    pub dest_chain: String,
+    pub dest_user: String,
+    pub min_return: U128,
+    pub output_token: String,
+    pub resolver: Option<AccountId>,
+    pub withdrawn: bool,
+    pub refunded: bool,
+}
+
+impl Escrow {
+    /// Withdrawn or refunded escrows can no longer move funds
+    pub fn is_closed(&self) -> bool {
+        self.withdrawn || self.refunded
+    }
+}
+
+#[cfg(feature = "escrow")]
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct HTLCEscrow {
+    pub escrows: UnorderedMap<u64, Escrow>,
+    pub escrow_counter: u64,
+    pub storage: StorageLedger,
+}
+
+#[cfg(feature = "escrow")]
+#[near_bindgen]
+impl HTLCEscrow {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            escrows: UnorderedMap::new(b"e"),
+            escrow_counter: 0,
+            storage: StorageLedger::new(b"s"),
+        }
+    }
+
+    /// NEP-145: add storage deposit for `account_id`, the caller by default
+    #[payable]
+    pub fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        self.storage.deposit(account_id, registration_only.unwrap_or(false))
+    }
+
+    /// NEP-145: withdraw unused storage deposit, all of it when `amount` is `None`
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.storage.withdraw(amount)
+    }
+
+    /// NEP-145: close the caller's storage account and refund its deposit. The account
+    /// must not have any open escrows, `force` can't remove them.
+    #[payable]
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.storage.unregister(force.unwrap_or(false))
+    }
+
+    /// Lock transferred tokens (NEP-141 receiver, called by the token contract). The
+    /// sender must sign the transaction and pays for the escrow's storage.
+    pub fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let lock: LockMsg = serde_json::from_str(&msg).expect("Invalid lock message");
+        self.create_escrow(sender_id, Some(env::predecessor_account_id()), amount, lock);
+
+        // All tokens are kept in the escrow
+        PromiseOrValue::Value(U128(0))
+    }
+
+    /// Lock the attached NEAR, refunding to `refund_to` (or the caller) on expiry. The
+    /// refund account must sign the transaction and pays for the escrow's storage.
+    #[payable]
+    pub fn lock_near(&mut self, lock: LockMsg, refund_to: Option<AccountId>) -> u64 {
+        let amount = U128(env::attached_deposit().as_yoctonear());
+        let sender = refund_to.unwrap_or_else(env::predecessor_account_id);
+        self.create_escrow(sender, None, amount, lock)
+    }
+
+    /// Register the caller as the resolver of an escrow that has none yet. The
+    /// resolver cannot be replaced afterwards.
+    pub fn register_resolver(&mut self, id: u64) {
+        let mut escrow = self.escrows.get(&id).expect("Escrow not found");
+        assert!(!escrow.is_closed(), "Escrow already closed");
+
+        let now = env::block_timestamp() / NANOS_PER_SECOND;
+        assert!(now < escrow.timelock_recovery, "Escrow expired");
+
+        let resolver_id = env::predecessor_account_id();
+        if let Some(current) = &escrow.resolver {
+            assert!(*current == resolver_id, "Resolver already registered");
+        }
+
+        escrow.resolver = Some(resolver_id.clone());
+        self.escrows.insert(&id, &escrow);
+
+        Event::ResolverRegistered { escrow_id: id, resolver_id }.emit();
+    }
+
+    /// Release the locked tokens to the resolver by revealing the hex-encoded preimage
+    ///
+    /// Only the resolver can call this during the exclusive period. After it, anyone
+    /// can trigger the withdrawal, but the funds still go to the resolver.
+    pub fn withdraw(&mut self, id: u64, preimage: String) -> Promise {
+        let mut escrow = self.escrows.get(&id).expect("Escrow not found");
+        assert!(!escrow.is_closed(), "Escrow already closed");
+
+        let resolver_id = escrow.resolver.clone().expect("No resolver registered");
+        let now = env::block_timestamp() / NANOS_PER_SECOND;
+        assert!(
+            env::predecessor_account_id() == resolver_id || now >= escrow.timelock_exclusive,
+            "Only resolver can withdraw"
+        );
+        assert!(now < escrow.timelock_recovery, "Escrow expired");
+
+        let secret = hex::decode(&preimage).expect("Invalid preimage");
+        assert_eq!(env::sha256(&secret), escrow.hashlock, "Invalid preimage");
+
+        escrow.withdrawn = true;
+        self.escrows.insert(&id, &escrow);
+
+        Event::EscrowWithdrawn {
+            escrow_id: id,
+            resolver_id: resolver_id.clone(),
+        }
+        .emit();
+
+        self.transfer_out(id, &escrow, resolver_id)
+    }
+
+    /// Return the locked funds to the sender once the recovery time has passed
+    pub fn refund(&mut self, id: u64) -> Promise {
+        let mut escrow = self.escrows.get(&id).expect("Escrow not found");
+        assert!(!escrow.is_closed(), "Escrow already closed");
+
+        let now = env::block_timestamp() / NANOS_PER_SECOND;
+        assert!(now >= escrow.timelock_recovery, "Escrow not expired");
+
+        escrow.refunded = true;
+        self.escrows.insert(&id, &escrow);
+
+        let sender = escrow.sender.clone();
+        Event::EscrowRefunded {
+            escrow_id: id,
+            sender: sender.clone(),
+        }
+        .emit();
+
+        self.transfer_out(id, &escrow, sender)
+    }
+
+    /// Remove the escrow once its funds are out, crediting the sender's storage, or
+    /// reopen it if the outgoing transfer failed
+    #[private]
+    pub fn resolve_transfer(&mut self, id: u64) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                if let Some(escrow) = self.escrows.get(&id) {
+                    let initial_storage = env::storage_usage();
+                    self.escrows.remove(&id);
+                    self.storage.settle(&escrow.sender, initial_storage);
+                }
+                true
+            }
+            _ => {
+                if let Some(mut escrow) = self.escrows.get(&id) {
+                    escrow.withdrawn = false;
+                    escrow.refunded = false;
+                    self.escrows.insert(&id, &escrow);
+                }
+                Event::EscrowTransferFailed { escrow_id: id }.emit();
+                false
+            }
+        }
+    }
+
+    /// Get escrow details
+    pub fn get_escrow(&self, id: u64) -> Option<Escrow> {
+        self.escrows.get(&id)
+    }
+
+    /// Get the number of escrows created so far
+    pub fn get_escrow_count(&self) -> u64 {
+        self.escrow_counter
+    }
+
+    /// NEP-145: storage balance of an account, if registered
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage.balance_of(&account_id)
+    }
+
+    /// NEP-145: minimum deposit to register and no maximum
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageLedger::bounds()
+    }
+
+    /// Store a new escrow and return its id
+    ///
+    /// The record is charged to the sender's storage deposit until it is closed. The
+    /// sender must have signed the transaction, so a lock can't be billed to someone
+    /// else, e.g. by calling `ft_on_transfer` directly.
+    fn create_escrow(
+        &mut self,
+        sender: AccountId,
+        token: Option<AccountId>,
+        amount: U128,
+        lock: LockMsg,
+    ) -> u64 {
+        assert!(amount.0 > 0, "Invalid amount");
+        assert_eq!(sender, env::signer_account_id(), "Sender must sign the lock");
+
+        let hashlock = hex::decode(&lock.hashlock).expect("Invalid hashlock");
+        assert_eq!(hashlock.len(), 32, "Hashlock must be 32 bytes");
+        assert!(lock.timelock > 0, "Invalid timelock");
+        let exclusive_period = lock.exclusive_period.unwrap_or(lock.timelock);
+        assert!(exclusive_period <= lock.timelock, "Exclusive period exceeds timelock");
+
+        let now = env::block_timestamp() / NANOS_PER_SECOND;
+        let timelock_exclusive = now + exclusive_period;
+        let timelock_recovery = now + lock.timelock;
+
+        let id = self.escrow_counter;
+        self.escrow_counter += 1;
+
+        let escrow = Escrow {
+            sender,
+            token,
+            amount,
+            hashlock,
+            timelock_exclusive,
+            timelock_recovery,
+            dest_chain: lock.dest_chain,
+            dest_user: lock.dest_user,
+            min_return: lock.min_return,
+            output_token: lock.output_token,
+            resolver: lock.resolver_id,
+            withdrawn: false,
+            refunded: false,
+        };
+
+        let initial_storage = env::storage_usage();
+        self.escrows.insert(&id, &escrow);
+        self.storage.settle(&escrow.sender, initial_storage);
+
+        Event::EscrowCreated {
+            escrow_id: id,
+            sender: escrow.sender,
+            token: escrow.token,
+            amount,
+            hashlock: lock.hashlock,
+        }
+        .emit();
+
+        id
+    }
+
+    /// Send the escrowed funds to `receiver_id` and resolve the result in a callback
+    fn transfer_out(&self, id: u64, escrow: &Escrow, receiver_id: AccountId) -> Promise {
+        let transfer = match &escrow.token {
+            Some(token) => ext_ft::ext(token.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(receiver_id, escrow.amount, Some(format!("Escrow {}", id))),
+            None => Promise::new(receiver_id).transfer(NearToken::from_yoctonear(escrow.amount.0)),
+        };
+
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                .resolve_transfer(id),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "escrow"))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    const SECRET: [u8; 32] = [7u8; 32];
+
+    fn alice() -> AccountId {
+        "alice.testnet".parse().unwrap()
+    }
+
+    fn resolver() -> AccountId {
+        "resolver.testnet".parse().unwrap()
+    }
+
+    fn token() -> AccountId {
+        "usdc.testnet".parse().unwrap()
+    }
+
+    fn set_context(predecessor: &AccountId, timestamp_secs: u64, deposit: u128) {
+        let mut context = VMContextBuilder::new();
+        context
+            .predecessor_account_id(predecessor.clone())
+            .signer_account_id(predecessor.clone())
+            .block_timestamp(timestamp_secs * NANOS_PER_SECOND)
+            .attached_deposit(NearToken::from_yoctonear(deposit));
+        testing_env!(context.build());
+    }
+
+    /// `token` calling `ft_on_transfer` in a transaction signed by `signer`
+    fn set_token_context(token: &AccountId, signer: &AccountId) {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(token.clone()).signer_account_id(signer.clone());
+        testing_env!(context.build());
+    }
+
+    /// Deposits 0.1 NEAR of storage for `account_id`
+    fn deposit_storage(contract: &mut HTLCEscrow, account_id: &AccountId) {
+        set_context(account_id, 0, NearToken::from_millinear(100).as_yoctonear());
+        contract.storage_deposit(None, None);
+    }
+
+    fn transfer_result(result: PromiseResult) {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result]
+        );
+    }
+
+    fn lock_msg(resolver_id: Option<AccountId>) -> LockMsg {
+        LockMsg {
+            hashlock: hex::encode(env::sha256(&SECRET)),
+            timelock: 100,
+            exclusive_period: Some(60),
+            dest_chain: "ethereum".to_string(),
+            dest_user: "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+            min_return: U128(1),
+            output_token: "ETH".to_string(),
+            resolver_id,
+        }
+    }
+
+    fn setup() -> (HTLCEscrow, u64) {
+        set_context(&alice(), 0, 0);
+        let mut contract = HTLCEscrow::new();
+        deposit_storage(&mut contract, &alice());
+        set_context(&alice(), 0, 1_000);
+        let id = contract.lock_near(lock_msg(Some(resolver())), None);
+        (contract, id)
+    }
+
+    #[test]
+    fn lock_near_creates_escrow() {
+        let (contract, id) = setup();
+        let escrow = contract.get_escrow(id).unwrap();
+        assert_eq!(escrow.sender, alice());
+        assert_eq!(escrow.token, None);
+        assert_eq!(escrow.amount, U128(1_000));
+        assert_eq!(escrow.timelock_exclusive, 60);
+        assert_eq!(escrow.timelock_recovery, 100);
+        assert_eq!(contract.get_escrow_count(), 1);
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![Event::EscrowCreated {
+                escrow_id: id,
+                sender: alice(),
+                token: None,
+                amount: U128(1_000),
+                hashlock: hex::encode(env::sha256(&SECRET)),
+            }
+            .to_log()]
+        );
+    }
+
+    #[test]
+    fn ft_on_transfer_locks_tokens() {
+        set_context(&alice(), 0, 0);
+        let mut contract = HTLCEscrow::new();
+        deposit_storage(&mut contract, &alice());
+        set_token_context(&token(), &alice());
+        let msg = serde_json::json!({
+            "hashlock": hex::encode(env::sha256(&SECRET)),
+            "timelock": 100,
+            "dest_chain": "ethereum",
+            "dest_user": "0x70997970C51812dc3A010C7d01b50e0d17dc79C8",
+            "min_return": "1",
+            "output_token": "ETH",
+            "resolver_id": null,
+        });
+        let result = contract.ft_on_transfer(alice(), U128(500), msg.to_string());
+        assert!(matches!(result, PromiseOrValue::Value(U128(0))));
+
+        let escrow = contract.get_escrow(0).unwrap();
+        assert_eq!(escrow.token, Some(token()));
+        assert_eq!(escrow.amount, U128(500));
+        // Refunds open after `timelock`, with the resolver exclusive until then
+        assert_eq!(escrow.timelock_exclusive, 100);
+        assert_eq!(escrow.timelock_recovery, 100);
+    }
+
+    #[test]
+    fn resolver_withdraws_with_preimage() {
+        let (mut contract, id) = setup();
+        set_context(&resolver(), 50, 0);
+        contract.withdraw(id, hex::encode(SECRET));
+        assert!(contract.get_escrow(id).unwrap().withdrawn);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid preimage")]
+    fn withdraw_rejects_wrong_preimage() {
+        let (mut contract, id) = setup();
+        set_context(&resolver(), 50, 0);
+        contract.withdraw(id, hex::encode([8u8; 32]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Resolver already registered")]
+    fn resolver_cannot_be_replaced_after_exclusivity() {
+        let (mut contract, id) = setup();
+        set_context(&alice(), 80, 0);
+        contract.register_resolver(id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Escrow expired")]
+    fn withdraw_rejected_after_timelock() {
+        let (mut contract, id) = setup();
+        set_context(&resolver(), 100, 0);
+        contract.withdraw(id, hex::encode(SECRET));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only resolver can withdraw")]
+    fn only_resolver_withdraws_during_exclusivity() {
+        let (mut contract, id) = setup();
+        set_context(&alice(), 50, 0);
+        contract.withdraw(id, hex::encode(SECRET));
+    }
+
+    #[test]
+    fn anyone_triggers_withdrawal_to_resolver_after_exclusivity() {
+        let (mut contract, id) = setup();
+        set_context(&alice(), 80, 0);
+        contract.withdraw(id, hex::encode(SECRET));
+        assert!(contract.get_escrow(id).unwrap().withdrawn);
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![Event::EscrowWithdrawn { escrow_id: id, resolver_id: resolver() }.to_log()]
+        );
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, resolver());
+    }
+
+    #[test]
+    #[should_panic(expected = "Escrow not expired")]
+    fn refund_waits_for_recovery() {
+        let (mut contract, id) = setup();
+        set_context(&alice(), 99, 0);
+        contract.refund(id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Escrow already closed")]
+    fn refund_rejected_after_withdraw() {
+        let (mut contract, id) = setup();
+        set_context(&resolver(), 50, 0);
+        contract.withdraw(id, hex::encode(SECRET));
+        set_context(&alice(), 100, 0);
+        contract.refund(id);
+    }
+
+    #[test]
+    fn refund_returns_near_to_sender() {
+        let (mut contract, id) = setup();
+        set_context(&resolver(), 100, 0);
+        contract.refund(id);
+        assert!(contract.get_escrow(id).unwrap().refunded);
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![Event::EscrowRefunded { escrow_id: id, sender: alice() }.to_log()]
+        );
+
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, alice());
+        assert!(matches!(
+            receipts[0].actions[..],
+            [near_sdk::mock::MockAction::Transfer { deposit, .. }] if deposit == NearToken::from_yoctonear(1_000)
+        ));
+        assert_eq!(receipts[1].receiver_id, env::current_account_id());
+    }
+
+    #[test]
+    fn refund_returns_tokens_to_sender() {
+        set_context(&alice(), 0, 0);
+        let mut contract = HTLCEscrow::new();
+        deposit_storage(&mut contract, &alice());
+        set_token_context(&token(), &alice());
+        let msg = serde_json::to_string(&lock_msg(None)).unwrap();
+        contract.ft_on_transfer(alice(), U128(500), msg);
+
+        set_context(&alice(), 100, 0);
+        contract.refund(0);
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, token());
+        match &receipts[0].actions[..] {
+            [near_sdk::mock::MockAction::FunctionCallWeight { method_name, args, attached_deposit, .. }] => {
+                assert_eq!(method_name, b"ft_transfer");
+                assert_eq!(*attached_deposit, NearToken::from_yoctonear(1));
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["receiver_id"], "alice.testnet");
+                assert_eq!(args["amount"], "500");
+            }
+            actions => panic!("Not a single function call: {:?}", actions),
+        }
+    }
+
+    #[test]
+    fn failed_transfer_reopens_escrow() {
+        let (mut contract, id) = setup();
+        set_context(&alice(), 100, 0);
+        contract.refund(id);
+        assert!(contract.get_escrow(id).unwrap().refunded);
+
+        transfer_result(PromiseResult::Failed);
+        assert!(!contract.resolve_transfer(id));
+        assert!(!contract.get_escrow(id).unwrap().is_closed());
+    }
+
+    #[test]
+    fn closed_escrow_removal_credits_sender() {
+        let (mut contract, id) = setup();
+        set_context(&resolver(), 50, 0);
+        contract.withdraw(id, hex::encode(SECRET));
+
+        transfer_result(PromiseResult::Successful(vec![]));
+        assert!(contract.resolve_transfer(id));
+        assert!(contract.get_escrow(id).is_none());
+        let balance = contract.storage_balance_of(alice()).unwrap();
+        assert_eq!(balance.available.0, balance.total.0 - StorageLedger::bounds().min.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account not registered for storage")]
+    fn lock_near_requires_storage_deposit() {
+        set_context(&alice(), 0, 1_000);
+        let mut contract = HTLCEscrow::new();
+        contract.lock_near(lock_msg(Some(resolver())), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sender must sign the lock")]
+    fn lock_near_cannot_charge_another_refund_account() {
+        let (mut contract, _) = setup();
+        deposit_storage(&mut contract, &resolver());
+        set_context(&alice(), 0, 1_000);
+        contract.lock_near(lock_msg(None), Some(resolver()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Sender must sign the lock")]
+    fn ft_on_transfer_rejects_spoofed_sender() {
+        let (mut contract, _) = setup();
+        let attacker: AccountId = "attacker.testnet".parse().unwrap();
+        set_token_context(&attacker, &attacker);
+        let msg = serde_json::to_string(&lock_msg(None)).unwrap();
+        contract.ft_on_transfer(alice(), U128(500), msg);
+    }
+}
@@ -0,0 +1,136 @@
+use near_sdk::env;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::Serialize;
+use near_sdk::AccountId;
+
+use crate::access::{Role, Subsystem};
+
+/// NEP-297 standard name of the events logged by these contracts
+pub const EVENT_STANDARD: &str = "betswap";
+
+/// Version of the event payloads, bumped on breaking changes
+pub const EVENT_VERSION: &str = "1.0.0";
+
+/// Contract events, logged as `EVENT_JSON:{"standard":..,"version":..,"event":..,"data":{..}}`
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum Event {
+    // Access control
+    RoleGranted { account_id: AccountId, role: Role, by: AccountId },
+    RoleRevoked { account_id: AccountId, role: Role, by: AccountId },
+    OwnershipTransferred { old_owner_id: AccountId, new_owner_id: AccountId },
+    Paused { subsystem: Option<Subsystem>, by: AccountId },
+    Unpaused { subsystem: Option<Subsystem>, by: AccountId },
+
+    // NEP-145 storage
+    StorageDeposited { account_id: AccountId, amount: U128 },
+    StorageWithdrawn { account_id: AccountId, amount: U128 },
+    StorageUnregistered { account_id: AccountId, amount: U128 },
+
+    // Bet markets
+    TokenAccepted { token: AccountId },
+    TokenRemoved { token: AccountId },
+    OracleAdded { public_key: String },
+    OracleRemoved { public_key: String },
+    ArbitratorSet { arbitrator: Option<AccountId> },
+    DisputeConfigUpdated { dispute_period: U64, challenge_bond_bps: u32 },
+    BetEventCreated { event_id: String, token: Option<AccountId>, end_time: U64, outcomes: Vec<String> },
+    BetPlaced { event_id: String, account_id: AccountId, outcome: u32, amount: U128 },
+    BetRefused { event_id: String, sender_id: AccountId, amount: U128 },
+    CrossChainBetPlaced { bet_id: String, event_id: String, account_id: AccountId, outcome: u32, amount: U128, eth_address: String },
+    CrossChainBetRemoved { bet_id: String },
+    OracleReported { event_id: String, public_key: String, outcome: u32, confidence: u64, leading_votes: u32, threshold: u32 },
+    EventDisputed { event_id: String, dissenting_reports: u32 },
+    PredictionAdvisory { event_id: String, outcome: u32, confidence: u64, min_confidence: u64 },
+    EventResolved { event_id: String, outcome: u32, confidence: Option<u64>, resolution: String },
+    EventVoided { event_id: String },
+    OutcomeChallenged { event_id: String, challenger: AccountId, outcome: u32, bond: U128 },
+    ChallengeRefused { event_id: String, sender_id: AccountId, amount: U128 },
+    ChallengeRuled { event_id: String, upheld: bool, outcome: u32 },
+    WinningsClaimed { event_id: String, account_id: AccountId, amount: U128 },
+    RefundClaimed { event_id: String, account_id: AccountId, amount: U128 },
+    PayoutFailed { event_id: String, account_id: AccountId },
+    RewardsClaimed { account_id: AccountId, amount: U128 },
+
+    // Solvers and quotes
+    SolverRegistered { solver_id: AccountId },
+    SolverDeactivated { solver_id: AccountId },
+    SolverConfigUpdated { solver_id: AccountId },
+    TeeMeasurementAdded { measurement: String },
+    TeeMeasurementRemoved { measurement: String },
+    AttestationKeyAdded { attestation_key: String },
+    AttestationKeyRemoved { attestation_key: String },
+    QuoteRequested { request_id: String, requester: AccountId, from_token: String, to_token: String, from_amount: U128, deadline: U64 },
+    QuoteBid { request_id: String, solver_id: AccountId, to_amount: U128 },
+    QuoteRemoved { request_id: String },
+    QuoteGenerated { request_id: String, order_id: String, solver_id: AccountId, to_amount: U128 },
+    HtlcLockRequested { order_id: String, htlc_contract: AccountId, hashlock: String },
+    MetaOrderFilled { order_id: String, solver_id: AccountId, making_amount: U128, success: bool },
+    MetaOrderExecuted {
+        order_id: String,
+        solver_id: AccountId,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hashlock: Option<String>,
+    },
+    MetaOrderRemoved { order_id: String },
+
+    // HTLC escrow
+    EscrowCreated { escrow_id: u64, sender: AccountId, token: Option<AccountId>, amount: U128, hashlock: String },
+    ResolverRegistered { escrow_id: u64, resolver_id: AccountId },
+    EscrowWithdrawn { escrow_id: u64, resolver_id: AccountId },
+    EscrowRefunded { escrow_id: u64, sender: AccountId },
+    EscrowTransferFailed { escrow_id: u64 },
+}
+
+#[derive(Serialize)]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+impl Event {
+    /// NEP-297 log line of the event
+    pub fn to_log(&self) -> String {
+        let log = EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_VERSION,
+            event: self,
+        };
+        format!("EVENT_JSON:{}", serde_json::to_string(&log).unwrap())
+    }
+
+    pub fn emit(&self) {
+        env::log_str(&self.to_log());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logs_nep297_json() {
+        let event = Event::BetPlaced {
+            event_id: "event_a".to_string(),
+            account_id: "alice.testnet".parse().unwrap(),
+            outcome: 1,
+            amount: U128(100),
+        };
+        assert_eq!(
+            event.to_log(),
+            r#"EVENT_JSON:{"standard":"betswap","version":"1.0.0","event":"bet_placed","data":{"event_id":"event_a","account_id":"alice.testnet","outcome":1,"amount":"100"}}"#
+        );
+    }
+
+    #[test]
+    fn enums_serialize_in_snake_case() {
+        let event = Event::Paused {
+            subsystem: Some(Subsystem::Betting),
+            by: "owner.testnet".parse().unwrap(),
+        };
+        assert!(event.to_log().ends_with(r#""event":"paused","data":{"subsystem":"betting","by":"owner.testnet"}}"#));
+    }
+}
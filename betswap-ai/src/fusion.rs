@@ -0,0 +1,251 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, AccountId};
+
+use crate::MetaOrder;
+
+/// 20-byte EVM address
+pub type Address = [u8; 20];
+
+/// 1inch Aggregation Router v6 (Limit Order Protocol v4), same address on all EVM chains
+pub const AGGREGATION_ROUTER_V6: &str = "0x111111125421cA6dc452d289314280a0f8842A65";
+
+/// Rainbow Bridge ERC-20 of NEAR on Ethereum, the EVM side of `wrap.near`
+pub const BRIDGED_WNEAR: &str = "0x85F17Cf997934a597031b2E18a9aB6ebD4B9f6a4";
+
+/// EIP-712 domain of the Aggregation Router v6
+pub const DOMAIN_NAME: &str = "1inch Aggregation Router";
+pub const DOMAIN_VERSION: &str = "6";
+
+const EIP712_DOMAIN_TYPE: &[u8] =
+    b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+const ORDER_TYPE: &[u8] = b"Order(uint256 salt,address maker,address receiver,address makerAsset,address takerAsset,uint256 makingAmount,uint256 takingAmount,uint256 makerTraits)";
+
+/// 1inch Fusion order as verified by the Ethereum-side settlement contract.
+/// Addresses and 256-bit words are `0x`-prefixed hex strings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FusionOrder {
+    pub salt: String,
+    pub maker: String,
+    pub receiver: String,
+    pub maker_asset: String,
+    pub taker_asset: String,
+    pub making_amount: U128,
+    pub taking_amount: U128,
+    pub maker_traits: String,
+}
+
+impl FusionOrder {
+    /// Build the Fusion order for a meta-order. Both tokens must have an EVM address,
+    /// see `evm_address_of`. The salt is derived from the order id.
+    pub fn from_meta_order(meta_order: &MetaOrder, maker: &str, maker_traits: &str) -> Self {
+        let maker_asset = evm_address_of(&meta_order.from_token).expect("From token is not a bridged ERC-20");
+        let taker_asset = evm_address_of(&meta_order.to_token).expect("To token is not a bridged ERC-20");
+
+        Self {
+            salt: to_hex(&env::keccak256_array(meta_order.order_id.as_bytes())),
+            maker: to_hex(&parse_address(maker).expect("Invalid maker address")),
+            // Zero receiver means the maker receives the taker asset
+            receiver: to_hex(&[0u8; 20]),
+            maker_asset: to_hex(&maker_asset),
+            taker_asset: to_hex(&taker_asset),
+            making_amount: meta_order.from_amount,
+            taking_amount: meta_order.to_amount,
+            maker_traits: to_hex(&parse_word(maker_traits).expect("Invalid maker traits")),
+        }
+    }
+
+    /// EIP-712 struct hash of the order
+    pub fn struct_hash(&self) -> [u8; 32] {
+        let mut encoded = Vec::with_capacity(9 * 32);
+        encoded.extend_from_slice(&env::keccak256_array(ORDER_TYPE));
+        encoded.extend_from_slice(&parse_word(&self.salt).expect("Invalid salt"));
+        encoded.extend_from_slice(&encode_address(&parse_address(&self.maker).expect("Invalid maker")));
+        encoded.extend_from_slice(&encode_address(&parse_address(&self.receiver).expect("Invalid receiver")));
+        encoded.extend_from_slice(&encode_address(&parse_address(&self.maker_asset).expect("Invalid maker asset")));
+        encoded.extend_from_slice(&encode_address(&parse_address(&self.taker_asset).expect("Invalid taker asset")));
+        encoded.extend_from_slice(&encode_uint(self.making_amount.0));
+        encoded.extend_from_slice(&encode_uint(self.taking_amount.0));
+        encoded.extend_from_slice(&parse_word(&self.maker_traits).expect("Invalid maker traits"));
+        env::keccak256_array(&encoded)
+    }
+
+    /// EIP-712 digest the maker signs for the Aggregation Router on `chain_id`
+    pub fn hash(&self, chain_id: u64, verifying_contract: &Address) -> [u8; 32] {
+        let domain = domain_separator(DOMAIN_NAME, DOMAIN_VERSION, chain_id, verifying_contract);
+        typed_data_hash(&domain, &self.struct_hash())
+    }
+}
+
+/// EIP-712 domain separator
+pub fn domain_separator(name: &str, version: &str, chain_id: u64, verifying_contract: &Address) -> [u8; 32] {
+    let mut encoded = Vec::with_capacity(5 * 32);
+    encoded.extend_from_slice(&env::keccak256_array(EIP712_DOMAIN_TYPE));
+    encoded.extend_from_slice(&env::keccak256_array(name.as_bytes()));
+    encoded.extend_from_slice(&env::keccak256_array(version.as_bytes()));
+    encoded.extend_from_slice(&encode_uint(chain_id as u128));
+    encoded.extend_from_slice(&encode_address(verifying_contract));
+    env::keccak256_array(&encoded)
+}
+
+/// `keccak256("\x19\x01" || domainSeparator || structHash)`
+pub fn typed_data_hash(domain_separator: &[u8; 32], struct_hash: &[u8; 32]) -> [u8; 32] {
+    let mut encoded = Vec::with_capacity(2 + 2 * 32);
+    encoded.extend_from_slice(b"\x19\x01");
+    encoded.extend_from_slice(domain_separator);
+    encoded.extend_from_slice(struct_hash);
+    env::keccak256_array(&encoded)
+}
+
+/// EVM address of a Rainbow Bridge token account (`<40 hex chars>.factory.bridge.near`),
+/// or of `wrap.near`, which is bridged as `BRIDGED_WNEAR`
+pub fn evm_address_of(token: &AccountId) -> Option<Address> {
+    if token.as_str() == "wrap.near" {
+        return parse_address(BRIDGED_WNEAR);
+    }
+    let prefix = token.as_str().split('.').next()?;
+    if prefix.len() != 40 {
+        return None;
+    }
+    parse_address(prefix)
+}
+
+/// Parse a hex address with or without `0x`
+pub fn parse_address(value: &str) -> Option<Address> {
+    let bytes = hex::decode(value.strip_prefix("0x").unwrap_or(value)).ok()?;
+    bytes.try_into().ok()
+}
+
+/// Parse a hex uint256 with or without `0x`, left-padding to 32 bytes
+pub fn parse_word(value: &str) -> Option<[u8; 32]> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.len() > 64 {
+        return None;
+    }
+    let bytes = hex::decode(format!("{:0>64}", digits)).ok()?;
+    bytes.try_into().ok()
+}
+
+/// `0x`-prefixed lowercase hex
+pub fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn encode_address(address: &Address) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address);
+    word
+}
+
+fn encode_uint(value: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example from the EIP-712 specification
+    #[test]
+    fn eip712_spec_mail_example() {
+        let contract = parse_address("0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC").unwrap();
+        let domain = domain_separator("Ether Mail", "1", 1, &contract);
+        assert_eq!(
+            hex::encode(domain),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+
+        let struct_hash = parse_word("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e").unwrap();
+        assert_eq!(
+            hex::encode(typed_data_hash(&domain, &struct_hash)),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn aggregation_router_v6_domain() {
+        let router = parse_address(AGGREGATION_ROUTER_V6).unwrap();
+        assert_eq!(
+            hex::encode(domain_separator(DOMAIN_NAME, DOMAIN_VERSION, 1, &router)),
+            "d999e213f11c7bfa3e796c3409e316f25e02aa3e25e5c207a92e381c7d22b6de"
+        );
+    }
+
+    /// Synthetic order, not taken from the 1inch SDK or an on-chain fill. The expected hashes were
+    /// computed with a standalone keccak-256/EIP-712 encoder, so this pins the encoding but still
+    /// needs a real Aggregation Router v6 order hash to check against.
+    #[test]
+    fn synthetic_fusion_order_hash() {
+        let order = FusionOrder {
+            salt: "0x0000000000000000000000000000000000000000000000000000018f2a5c7b11".to_string(),
+            maker: "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+            receiver: "0x0000000000000000000000000000000000000000".to_string(),
+            maker_asset: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string(),
+            taker_asset: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
+            making_amount: U128(1_000_000_000),
+            taking_amount: U128(300_000_000_000_000_000),
+            maker_traits: "0x4000000000000000000000000000000000000000000000000000000000000000".to_string(),
+        };
+        assert_eq!(hex::encode(order.struct_hash()), "c428dfe063514abac7c22effe3620fc5eea54aca886c20dfaa750cd1ae9a0bbe");
+
+        let router = parse_address(AGGREGATION_ROUTER_V6).unwrap();
+        assert_eq!(hex::encode(order.hash(1, &router)), "fd0683f2ebf450568ee497532dc01b064b4e1d8964b5ad81f97e43a8b7746761");
+        assert_eq!(hex::encode(order.hash(137, &router)), "82654c999795b72730938f125141399b33837c57eedb4ce42da7d17029887c5c");
+    }
+
+    #[test]
+    fn bridged_token_addresses() {
+        let usdc: AccountId = "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48.factory.bridge.near".parse().unwrap();
+        assert_eq!(
+            to_hex(&evm_address_of(&usdc).unwrap()),
+            "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        );
+
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        assert_eq!(evm_address_of(&wnear), parse_address(BRIDGED_WNEAR));
+
+        let native: AccountId = "wrap.testnet".parse().unwrap();
+        assert_eq!(evm_address_of(&native), None);
+    }
+
+    #[test]
+    fn from_meta_order_maps_bridged_tokens() {
+        let meta_order = MetaOrder {
+            order_id: "order_0".to_string(),
+            from_token: "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48.factory.bridge.near".parse().unwrap(),
+            to_token: "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2.factory.bridge.near".parse().unwrap(),
+            from_amount: U128(1_000_000),
+            to_amount: U128(990_000),
+            deadline: near_sdk::json_types::U64(0),
+            intent_id: "intent_1".to_string(),
+            signature: crate::ChainSignature {
+                signature: String::new(),
+                public_key: String::new(),
+                message: String::new(),
+            },
+            solver_id: "solver.testnet".parse().unwrap(),
+            partial_fill: None,
+            hashlock: None,
+            filled_amount: U128(0),
+            remaining_amount: U128(1_000_000),
+            is_executed: false,
+        };
+
+        let order = FusionOrder::from_meta_order(&meta_order, "0x70997970C51812dc3A010C7d01b50e0d17dc79C8", "0x0");
+        assert_eq!(order.maker, "0x70997970c51812dc3a010c7d01b50e0d17dc79c8");
+        assert_eq!(order.maker_asset, "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        assert_eq!(order.taker_asset, "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        assert_eq!(order.making_amount, U128(1_000_000));
+        assert_eq!(order.taking_amount, U128(990_000));
+        assert_eq!(order.salt, to_hex(&env::keccak256_array(b"order_0")));
+    }
+
+    #[test]
+    fn parse_word_pads_and_rejects_overflow() {
+        assert_eq!(parse_word("0x1").unwrap()[31], 1);
+        assert_eq!(parse_word(&"f".repeat(65)), None);
+    }
+}
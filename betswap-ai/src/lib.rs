@@ -1,12 +1,37 @@
+// Contract methods map 1:1 to JSON call arguments
+#![allow(clippy::too_many_arguments)]
+
+// Each contract is deployed to its own account, so a wasm build must export only one of them
+#[cfg(all(
+    target_arch = "wasm32",
+    any(
+        all(feature = "solver", feature = "betswap"),
+        all(feature = "solver", feature = "escrow"),
+        all(feature = "betswap", feature = "escrow"),
+    )
+))]
+compile_error!("Build one contract per wasm with `--no-default-features --features <solver|betswap|escrow>`");
+
+pub mod access;
+#[cfg(feature = "betswap")]
+pub mod bet_swap_ai;
+pub mod escrow;
+pub mod events;
+pub mod fusion;
+pub mod merkle;
+pub mod signature;
+#[cfg(feature = "solver")]
+pub mod solver;
+pub mod storage;
+pub mod tee;
+
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LookupMap, UnorderedMap};
 use near_sdk::json_types::{U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, AccountId, Balance, Gas, PanicOnDefault, Promise, PromiseResult};
-use std::collections::HashMap;
+use near_sdk::AccountId;
 
-/// Gas for cross-contract calls
-const GAS_FOR_CROSS_CONTRACT_CALL: Gas = Gas(5_000_000_000_000);
+#[cfg(feature = "solver")]
+pub use crate::solver::ShadeAgentSolver;
 
 /// NEAR Chain Signature verification
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
@@ -27,22 +52,89 @@ pub struct MetaOrder {
     pub deadline: U64,
     pub intent_id: String,
     pub signature: ChainSignature,
+    pub solver_id: AccountId,
+    pub partial_fill: Option<PartialFillConfig>,
+    pub hashlock: Option<String>, // Hex sha256 of the user's secret, when not partially fillable
+    pub filled_amount: U128,
+    pub remaining_amount: U128,
     pub is_executed: bool,
 }
 
+/// Partial fill setup of an order, as in 1inch Fusion+: the order is split into
+/// `parts` equal chunks and each fill reveals one of `parts + 1` secrets committed
+/// to by `secrets_merkle_root` (hex). Leaves are built with `merkle::leaf`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+pub struct PartialFillConfig {
+    pub secrets_merkle_root: String,
+    pub parts: u32,
+}
+
+/// A single fill of a meta-order
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+pub struct MetaOrderFill {
+    pub solver_id: AccountId,
+    pub making_amount: U128,
+    pub taking_amount: U128,
+    pub filled_amount: U128, // Total filled after this fill
+    pub secret_index: u32,
+    pub hashlock: String,
+    pub timestamp: U64,
+}
+
+/// Dutch-auction curve for the required `to_amount`, ported from `DutchAuction.sol`.
+/// The price starts at `start_amount` and drops by `step_amount` every `step_time`
+/// nanoseconds until it reaches `min_amount`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+pub struct DutchAuctionCurve {
+    pub start_amount: U128,
+    pub min_amount: U128,
+    pub step_time: U64,
+    pub step_amount: U128,
+}
+
+impl DutchAuctionCurve {
+    /// Required amount `elapsed` nanoseconds after the auction started
+    pub fn price_at(&self, elapsed: u64) -> u128 {
+        let steps = (elapsed / self.step_time.0) as u128;
+        let price_reduction = steps.saturating_mul(self.step_amount.0);
+
+        if price_reduction >= self.start_amount.0 - self.min_amount.0 {
+            return self.min_amount.0;
+        }
+
+        self.start_amount.0 - price_reduction
+    }
+}
+
 /// Quote Request structure
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
 pub struct QuoteRequest {
     pub request_id: String,
+    pub requester: AccountId,
     pub from_token: AccountId,
     pub to_token: AccountId,
     pub from_amount: U128,
     pub to_amount: U128,
     pub deadline: U64,
+    pub created_at: U64,
+    pub bidding_end: U64,
+    pub auction: Option<DutchAuctionCurve>,
+    pub partial_fill: Option<PartialFillConfig>,
+    pub hashlock: Option<String>, // Hex sha256 of the user's secret, when not partially fillable
     pub intent_id: String,
     pub is_executed: bool,
 }
 
+/// Solver bid on a quote request
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+pub struct QuoteBid {
+    pub solver_id: AccountId,
+    pub to_amount: U128, // Net of the solver fee
+    pub intent_id: String,
+    pub signature: ChainSignature,
+    pub submitted_at: U64,
+}
+
 /// Solver configuration
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
 pub struct SolverConfig {
@@ -75,317 +167,31 @@ pub struct Intent {
     pub status: String, // "pending", "executed", "failed"
 }
 
-#[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-pub struct ShadeAgentSolver {
-    pub owner_id: AccountId,
-    pub quote_requests: UnorderedMap<String, QuoteRequest>,
-    pub meta_orders: UnorderedMap<String, MetaOrder>,
-    pub solvers: UnorderedMap<AccountId, SolverConfig>,
-    pub intents: UnorderedMap<String, Intent>,
-    pub tee_configs: UnorderedMap<AccountId, TEEConfig>,
-    pub quote_counter: u64,
-    pub order_counter: u64,
-    pub solver_reputation: UnorderedMap<AccountId, u32>,
-}
-
-#[near_bindgen]
-impl ShadeAgentSolver {
-    #[init]
-    pub fn new(owner_id: AccountId) -> Self {
-        Self {
-            owner_id,
-            quote_requests: UnorderedMap::new(b"q"),
-            meta_orders: UnorderedMap::new(b"m"),
-            solvers: UnorderedMap::new(b"s"),
-            intents: UnorderedMap::new(b"i"),
-            tee_configs: UnorderedMap::new(b"t"),
-            quote_counter: 0,
-            order_counter: 0,
-            solver_reputation: UnorderedMap::new(b"r"),
-        }
-    }
-
-    /// Register a new solver with TEE configuration
-    pub fn register_solver(
-        &mut self,
-        solver_address: AccountId,
-        min_quote_amount: U128,
-        max_quote_amount: U128,
-        fee_percentage: u32,
-        tee_config: TEEConfig,
-    ) {
-        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can register solvers");
-        assert!(fee_percentage <= 1000, "Fee percentage too high"); // Max 10%
-
-        // Verify TEE attestation
-        assert!(self.verify_tee_attestation(&tee_config), "Invalid TEE attestation");
-
-        let solver_config = SolverConfig {
-            solver_address: solver_address.clone(),
-            min_quote_amount,
-            max_quote_amount,
-            fee_percentage,
-            is_active: true,
-            reputation: 1000, // Initial reputation
-        };
-
-        self.solvers.insert(&solver_address, &solver_config);
-        self.tee_configs.insert(&solver_address, &tee_config);
-        self.solver_reputation.insert(&solver_address, &1000);
-    }
-
-    /// Request a quote for cross-chain swap
-    pub fn request_quote(
-        &mut self,
-        from_token: AccountId,
-        to_token: AccountId,
-        from_amount: U128,
-        deadline: U64,
-    ) -> String {
-        assert!(from_amount.0 > 0, "Invalid amount");
-        assert!(deadline.0 > env::block_timestamp(), "Invalid deadline");
-
-        let request_id = format!("quote_{}", self.quote_counter);
-        self.quote_counter += 1;
-
-        let quote_request = QuoteRequest {
-            request_id: request_id.clone(),
-            from_token,
-            to_token,
-            from_amount,
-            to_amount: U128(0), // Will be set by solver
-            deadline,
-            intent_id: String::new(),
-            is_executed: false,
-        };
-
-        self.quote_requests.insert(&request_id, &quote_request);
-
-        // Emit event for solvers to listen
-        env::log_str(&format!("Quote requested: {}", request_id));
-
-        request_id
-    }
-
-    /// Generate quote and create meta-order (called by solver)
-    pub fn generate_quote(
-        &mut self,
-        request_id: String,
-        to_amount: U128,
-        intent_id: String,
-        signature: ChainSignature,
-    ) -> String {
-        let solver_id = env::predecessor_account_id();
-        
-        // Verify solver is registered and active
-        let solver_config = self.solvers.get(&solver_id).expect("Solver not registered");
-        assert!(solver_config.is_active, "Solver not active");
-
-        // Verify TEE configuration
-        let tee_config = self.tee_configs.get(&solver_id).expect("TEE config not found");
-        assert!(tee_config.is_verified, "TEE not verified");
-
-        let quote_request = self.quote_requests.get(&request_id).expect("Request not found");
-        assert!(env::block_timestamp() <= quote_request.deadline.0, "Quote expired");
-        assert!(!quote_request.is_executed, "Request already executed");
-
-        // Validate quote amount
-        assert!(to_amount.0 >= solver_config.min_quote_amount.0, "Quote too low");
-        assert!(to_amount.0 <= solver_config.max_quote_amount.0, "Quote too high");
-
-        // Calculate fee
-        let fee = (to_amount.0 * solver_config.fee_percentage as u128) / 10000;
-        let final_amount = to_amount.0 - fee;
-
-        // Create meta-order
-        let order_id = format!("order_{}", self.order_counter);
-        self.order_counter += 1;
-
-        let meta_order = MetaOrder {
-            order_id: order_id.clone(),
-            from_token: quote_request.from_token.clone(),
-            to_token: quote_request.to_token.clone(),
-            from_amount: quote_request.from_amount.clone(),
-            to_amount: U128(final_amount),
-            deadline: quote_request.deadline.clone(),
-            intent_id: intent_id.clone(),
-            signature,
-            is_executed: false,
-        };
-
-        self.meta_orders.insert(&order_id, &meta_order);
-
-        // Update quote request
-        let mut updated_request = quote_request.clone();
-        updated_request.to_amount = U128(final_amount);
-        updated_request.intent_id = intent_id;
-        self.quote_requests.insert(&request_id, &updated_request);
+/// Page size of paginated views when no `limit` is given
+pub const DEFAULT_PAGE_LIMIT: u64 = 50;
 
-        // Create intent
-        let intent = Intent {
-            intent_id: intent_id.clone(),
-            user_id: env::predecessor_account_id(),
-            from_token: quote_request.from_token.clone(),
-            to_token: quote_request.to_token.clone(),
-            from_amount: quote_request.from_amount.clone(),
-            to_amount: U128(final_amount),
-            deadline: quote_request.deadline.clone(),
-            status: "pending".to_string(),
-        };
+/// Largest page a paginated view returns
+pub const MAX_PAGE_LIMIT: u64 = 100;
 
-        self.intents.insert(&intent_id, &intent);
-
-        env::log_str(&format!("Quote generated: {} -> {}", request_id, order_id));
-
-        order_id
-    }
-
-    /// Execute meta-order using NEAR Chain Signatures
-    pub fn execute_meta_order(&mut self, order_id: String, secret: String) -> bool {
-        let meta_order = self.meta_orders.get(&order_id).expect("Order not found");
-        assert!(env::block_timestamp() <= meta_order.deadline.0, "Order expired");
-        assert!(!meta_order.is_executed, "Order already executed");
-
-        // Verify NEAR Chain Signature
-        assert!(self.verify_near_signature(&meta_order.signature, &meta_order.intent_id), "Invalid NEAR signature");
-
-        // Execute cross-chain swap using HTLC
-        let success = self.execute_cross_chain_swap(&meta_order, &secret);
-
-        if success {
-            // Mark order as executed
-            let mut updated_order = meta_order.clone();
-            updated_order.is_executed = true;
-            self.meta_orders.insert(&order_id, &updated_order);
-
-            // Update intent status
-            let mut intent = self.intents.get(&meta_order.intent_id).expect("Intent not found");
-            intent.status = "executed".to_string();
-            self.intents.insert(&meta_order.intent_id, &intent);
-
-            // Update solver reputation
-            self.update_solver_reputation(&env::predecessor_account_id(), true);
-        }
-
-        env::log_str(&format!("Meta order executed: {} -> {}", order_id, success));
-
-        success
-    }
-
-    /// Verify TEE attestation report
-    fn verify_tee_attestation(&self, tee_config: &TEEConfig) -> bool {
-        // In production, this would verify the TEE attestation report
-        // For demo purposes, we'll accept any non-empty attestation
-        !tee_config.attestation_report.is_empty()
-    }
-
-    /// Verify NEAR Chain Signature
-    fn verify_near_signature(&self, signature: &ChainSignature, intent_id: &str) -> bool {
-        // In production, this would verify the NEAR Chain Signature
-        // For demo purposes, we'll accept any non-empty signature
-        !signature.signature.is_empty() && !intent_id.is_empty()
-    }
-
-    /// Execute cross-chain swap using HTLC
-    fn execute_cross_chain_swap(&self, meta_order: &MetaOrder, secret: &str) -> bool {
-        // Create HTLC lock for cross-chain execution
-        let hashlock = env::sha256(secret.as_bytes());
-        let timelock = env::block_timestamp() + 7200000000000; // 2 hours in nanoseconds
-
-        // In production, this would call the HTLC contract
-        // For demo purposes, we'll simulate success
-        env::log_str(&format!("HTLC lock created for order: {}", meta_order.order_id));
-
-        true
-    }
-
-    /// Update solver reputation based on performance
-    fn update_solver_reputation(&mut self, solver_address: &AccountId, success: bool) {
-        let current_reputation = self.solver_reputation.get(solver_address).unwrap_or(1000);
-        
-        let new_reputation = if success {
-            current_reputation + 10 // Increase reputation
-        } else {
-            if current_reputation > 10 {
-                current_reputation - 10
-            } else {
-                0
-            }
-        };
-
-        self.solver_reputation.insert(solver_address, &new_reputation);
-    }
-
-    /// Get quote request details
-    pub fn get_quote_request(&self, request_id: String) -> Option<QuoteRequest> {
-        self.quote_requests.get(&request_id)
-    }
-
-    /// Get meta-order details
-    pub fn get_meta_order(&self, order_id: String) -> Option<MetaOrder> {
-        self.meta_orders.get(&order_id)
-    }
-
-    /// Get solver configuration
-    pub fn get_solver_config(&self, solver_address: AccountId) -> Option<SolverConfig> {
-        self.solvers.get(&solver_address)
-    }
-
-    /// Get intent details
-    pub fn get_intent(&self, intent_id: String) -> Option<Intent> {
-        self.intents.get(&intent_id)
-    }
-
-    /// Deactivate solver (only owner)
-    pub fn deactivate_solver(&mut self, solver_address: AccountId) {
-        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can deactivate solvers");
-        
-        if let Some(mut solver_config) = self.solvers.get(&solver_address) {
-            solver_config.is_active = false;
-            self.solvers.insert(&solver_address, &solver_config);
-        }
-    }
-
-    /// Update solver configuration (only owner)
-    pub fn update_solver_config(
-        &mut self,
-        solver_address: AccountId,
-        min_quote_amount: U128,
-        max_quote_amount: U128,
-        fee_percentage: u32,
-    ) {
-        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can update solver config");
-        assert!(fee_percentage <= 1000, "Fee percentage too high");
+/// Apply the `from_index`/`limit` arguments of a paginated view to `items`
+pub fn paginate<T>(items: impl Iterator<Item = T>, from_index: Option<U64>, limit: Option<U64>) -> Vec<T> {
+    let from_index = from_index.map_or(0, |index| index.0) as usize;
+    let limit = limit.map_or(DEFAULT_PAGE_LIMIT, |limit| limit.0.min(MAX_PAGE_LIMIT)) as usize;
+    items.skip(from_index).take(limit).collect()
+}
 
-        if let Some(mut solver_config) = self.solvers.get(&solver_address) {
-            solver_config.min_quote_amount = min_quote_amount;
-            solver_config.max_quote_amount = max_quote_amount;
-            solver_config.fee_percentage = fee_percentage;
-            self.solvers.insert(&solver_address, &solver_config);
-        }
-    }
+mod u256 {
+    // Lints fire inside the `uint` macro expansion
+    #![allow(clippy::assign_op_pattern, clippy::manual_div_ceil)]
 
-    /// Get solver reputation
-    pub fn get_solver_reputation(&self, solver_address: AccountId) -> u32 {
-        self.solver_reputation.get(&solver_address).unwrap_or(0)
-    }
-
-    /// List all active solvers
-    pub fn get_active_solvers(&self) -> Vec<AccountId> {
-        let mut active_solvers = Vec::new();
-        for (solver_address, config) in self.solvers.iter() {
-            if config.is_active {
-                active_solvers.push(solver_address);
-            }
-        }
-        active_solvers
+    uint::construct_uint! {
+        /// 256-bit integer for intermediate products of token amounts
+        pub struct U256(4);
     }
+}
+pub use u256::U256;
 
-    /// Get quote statistics
-    pub fn get_quote_stats(&self) -> (u64, u64) {
-        let total_requests = self.quote_counter;
-        let total_orders = self.order_counter;
-        (total_requests, total_orders)
-    }
-}
\ No newline at end of file
+/// `a * b / c` without overflowing on yocto-scale amounts, panics if `c` is zero
+pub fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    (U256::from(a) * U256::from(b) / U256::from(c)).as_u128()
+}
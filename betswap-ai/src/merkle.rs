@@ -0,0 +1,97 @@
+use near_sdk::env;
+
+/// Leaf for the secret at `index`: `sha256(index as u64 big-endian || sha256(secret))`
+pub fn leaf(index: u64, secret_hash: &[u8; 32]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(8 + 32);
+    data.extend_from_slice(&index.to_be_bytes());
+    data.extend_from_slice(secret_hash);
+    env::sha256_array(&data)
+}
+
+/// Hash two nodes in sorted order, so proofs don't need left/right flags
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = Vec::with_capacity(64);
+    data.extend_from_slice(first);
+    data.extend_from_slice(second);
+    env::sha256_array(&data)
+}
+
+/// Check that `leaf` is part of the tree with `root`
+pub fn verify_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed == *root
+}
+
+/// Root of the tree over `leaves`; an odd node is carried up unchanged
+pub fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    assert!(!leaves.is_empty(), "No leaves");
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Proof for the leaf at `index`, matching `root`
+pub fn proof(leaves: &[[u8; 32]], mut index: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            proof.push(level[sibling]);
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        index /= 2;
+    }
+    proof
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: u64) -> Vec<[u8; 32]> {
+        (0..count)
+            .map(|i| leaf(i, &env::sha256_array(&[i as u8; 32])))
+            .collect()
+    }
+
+    #[test]
+    fn every_leaf_proves_against_root() {
+        for count in 1..=6 {
+            let leaves = leaves(count);
+            let root = root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                assert!(verify_proof(&root, *leaf, &proof(&leaves, index)));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_index_or_secret() {
+        let leaves = leaves(5);
+        let root = root(&leaves);
+        let proof = proof(&leaves, 2);
+
+        let secret_hash = env::sha256_array(&[2u8; 32]);
+        assert!(verify_proof(&root, leaf(2, &secret_hash), &proof));
+        assert!(!verify_proof(&root, leaf(3, &secret_hash), &proof));
+        assert!(!verify_proof(&root, leaf(2, &env::sha256_array(&[9u8; 32])), &proof));
+    }
+}
@@ -0,0 +1,146 @@
+use near_sdk::{env, CurveType, PublicKey};
+use std::str::FromStr;
+
+/// Canonical message a NEAR Chain Signature must commit to for a meta-order
+pub fn order_message(intent_id: &str, from_amount: u128, to_amount: u128, deadline: u64) -> String {
+    format!("{}:{}:{}:{}", intent_id, from_amount, to_amount, deadline)
+}
+
+/// Canonical message an oracle signs to report the outcome of an event
+pub fn oracle_report_message(event_id: &str, outcome: u32, confidence: u64, timestamp: u64) -> String {
+    format!("{}:{}:{}:{}", event_id, outcome, confidence, timestamp)
+}
+
+/// Verify a hex-encoded signature over `message` for a NEAR-formatted public key
+/// (`ed25519:<base58>` or `secp256k1:<base58>`).
+///
+/// ed25519 signatures are 64 bytes over the raw message. secp256k1 signatures are
+/// 65 bytes `r || s || v` over the SHA-256 hash of the message.
+pub fn verify_signature(signature: &str, public_key: &str, message: &str) -> bool {
+    let public_key = match PublicKey::from_str(public_key) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+    let signature = match hex::decode(signature) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    // Drop the curve type prefix
+    let key_data = &public_key.as_bytes()[1..];
+
+    match public_key.curve_type() {
+        CurveType::ED25519 => {
+            let (Ok(signature), Ok(key_data)) = (
+                <[u8; 64]>::try_from(signature.as_slice()),
+                <[u8; 32]>::try_from(key_data),
+            ) else {
+                return false;
+            };
+            env::ed25519_verify(&signature, message.as_bytes(), &key_data)
+        }
+        CurveType::SECP256K1 => {
+            if signature.len() != 65 {
+                return false;
+            }
+            // Accept both raw (0/1) and Ethereum-style (27/28) recovery ids
+            let v = match signature[64] {
+                v @ 0..=1 => v,
+                v @ 27..=28 => v - 27,
+                _ => return false,
+            };
+            let hash = env::sha256(message.as_bytes());
+            env::ecrecover(&hash, &signature[..64], v, true)
+                .is_some_and(|recovered| recovered.as_slice() == key_data)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "intent_1:1000000:990000:1700000000000000000";
+
+    const ED25519_KEY: &str = "ed25519:8ocU7FbgmTDA4egSLPguXXPzm9MAfyPVP7m39Yc6ji6G";
+    const ED25519_SIGNATURE: &str = "69e81a9d312cedaff16f5c1630e01cd9d46d791a27bdc48cd2a21f3d3b11a604776be14e648bd0f152a30b7509bc3d080610ce755fbb1e252e854ba2069ea000";
+
+    const SECP256K1_KEY: &str = "secp256k1:44EBPZfYsVPZHRuXWuTR2RXuPzhaHLPkvhf4mqCGYgEJxwwvoAzrTPjttgEpiA123LH7rGq5emHS4uH5Pm7XQoRm";
+    const SECP256K1_SIGNATURE: &str = "b73366cd02d4d9bed7598e0b278fbcc9272107c85022af32da2b9d2be591181356f240d2cf7df42858239d65f1e043e36982445c3ba3b39d7dab20dbdbd53294";
+
+    #[test]
+    fn order_message_format() {
+        assert_eq!(
+            order_message("intent_1", 1_000_000, 990_000, 1_700_000_000_000_000_000),
+            MESSAGE
+        );
+    }
+
+    #[test]
+    fn oracle_report_message_format() {
+        assert_eq!(oracle_report_message("event_a", 1, 90, 2000), "event_a:1:90:2000");
+    }
+
+    #[test]
+    fn ed25519_accepts_valid_signature() {
+        assert!(verify_signature(ED25519_SIGNATURE, ED25519_KEY, MESSAGE));
+    }
+
+    #[test]
+    fn ed25519_rejects_tampered_message() {
+        assert!(!verify_signature(
+            ED25519_SIGNATURE,
+            ED25519_KEY,
+            "intent_1:1000000:990001:1700000000000000000"
+        ));
+    }
+
+    #[test]
+    fn ed25519_rejects_wrong_key() {
+        let other_key = "ed25519:DcA2MzgpJbrUATQLLceocVckhhAqrkingax4oJ9kZ847";
+        assert!(!verify_signature(ED25519_SIGNATURE, other_key, MESSAGE));
+    }
+
+    #[test]
+    fn secp256k1_accepts_valid_signature() {
+        assert!(verify_signature(
+            &format!("{}01", SECP256K1_SIGNATURE),
+            SECP256K1_KEY,
+            MESSAGE
+        ));
+        assert!(verify_signature(
+            &format!("{}1c", SECP256K1_SIGNATURE),
+            SECP256K1_KEY,
+            MESSAGE
+        ));
+    }
+
+    #[test]
+    fn secp256k1_rejects_wrong_recovery_id() {
+        assert!(!verify_signature(
+            &format!("{}00", SECP256K1_SIGNATURE),
+            SECP256K1_KEY,
+            MESSAGE
+        ));
+    }
+
+    #[test]
+    fn secp256k1_rejects_tampered_message() {
+        assert!(!verify_signature(
+            &format!("{}01", SECP256K1_SIGNATURE),
+            SECP256K1_KEY,
+            "intent_2:1000000:990000:1700000000000000000"
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(!verify_signature("", ED25519_KEY, MESSAGE));
+        assert!(!verify_signature("zz", ED25519_KEY, MESSAGE));
+        assert!(!verify_signature(
+            SECP256K1_SIGNATURE,
+            SECP256K1_KEY,
+            MESSAGE
+        ));
+        assert!(!verify_signature(ED25519_SIGNATURE, "not-a-key", MESSAGE));
+    }
+}
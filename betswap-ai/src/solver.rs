@@ -0,0 +1,2125 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap, UnorderedSet};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, Gas, NearToken, PanicOnDefault, Promise, PromiseResult};
+
+use crate::access::{AccessControl, PauseState, Role, Subsystem};
+use crate::escrow::LockMsg;
+use crate::events::Event;
+use crate::fusion::FusionOrder;
+use crate::storage::{StorageBalance, StorageBalanceBounds, StorageLedger};
+use crate::{fusion, merkle, signature, tee};
+use crate::{
+    paginate, ChainSignature, DutchAuctionCurve, Intent, MetaOrder, MetaOrderFill, PartialFillConfig, QuoteBid, QuoteRequest,
+    SolverConfig, TEEConfig,
+};
+
+/// Gas for cross-contract calls
+const GAS_FOR_CROSS_CONTRACT_CALL: Gas = Gas::from_tgas(20);
+
+/// Gas for the meta-order execution callback
+const GAS_FOR_EXECUTE_CALLBACK: Gas = Gas::from_tgas(20);
+
+/// HTLC lock duration for meta-order execution (2 hours in seconds)
+const HTLC_TIMELOCK: u64 = 7200;
+
+/// Default time solvers have to bid on a quote request (1 minute in nanoseconds)
+const DEFAULT_BIDDING_PERIOD: u64 = 60_000_000_000;
+
+/// wNEAR token accounts. Orders are locked with `lock_near`, so the output token
+/// must be NEAR-denominated for `to_amount` to mean the same thing in yoctoNEAR.
+const WNEAR_TOKENS: [&str; 2] = ["wrap.near", "wrap.testnet"];
+
+/// HTLC escrow contract interface
+#[ext_contract(ext_htlc)]
+pub trait HTLCContract {
+    fn lock_near(&mut self, lock: LockMsg, refund_to: Option<AccountId>) -> u64;
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct ShadeAgentSolver {
+    pub access: AccessControl,
+    pub pause: PauseState,
+    pub storage: StorageLedger,
+    pub htlc_contract: AccountId,
+    pub quote_requests: UnorderedMap<String, QuoteRequest>,
+    pub quote_bids: UnorderedMap<String, Vec<QuoteBid>>,
+    pub meta_orders: UnorderedMap<String, MetaOrder>,
+    pub order_fills: UnorderedMap<String, Vec<MetaOrderFill>>,
+    pub solvers: UnorderedMap<AccountId, SolverConfig>,
+    pub intents: UnorderedMap<String, Intent>,
+    pub user_intents: LookupMap<AccountId, Vec<String>>, // Intent ids by user
+    pub tee_configs: UnorderedMap<AccountId, TEEConfig>,
+    pub quote_counter: u64,
+    pub order_counter: u64,
+    pub solver_reputation: UnorderedMap<AccountId, u32>,
+    pub tee_measurements: UnorderedSet<String>,
+    pub attestation_keys: UnorderedSet<String>, // Pinned quoting enclave keys, hex x || y
+}
+
+#[near_bindgen]
+impl ShadeAgentSolver {
+    #[init]
+    pub fn new(owner_id: AccountId, htlc_contract: AccountId) -> Self {
+        Self {
+            access: AccessControl::new(owner_id, b"o"),
+            pause: PauseState::default(),
+            storage: StorageLedger::new(b"d"),
+            htlc_contract,
+            quote_requests: UnorderedMap::new(b"q"),
+            quote_bids: UnorderedMap::new(b"b"),
+            meta_orders: UnorderedMap::new(b"m"),
+            order_fills: UnorderedMap::new(b"f"),
+            solvers: UnorderedMap::new(b"s"),
+            intents: UnorderedMap::new(b"i"),
+            user_intents: LookupMap::new(b"u"),
+            tee_configs: UnorderedMap::new(b"t"),
+            quote_counter: 0,
+            order_counter: 0,
+            solver_reputation: UnorderedMap::new(b"r"),
+            tee_measurements: UnorderedSet::new(b"a"),
+            attestation_keys: UnorderedSet::new(b"k"),
+        }
+    }
+
+    /// Grant `role` to an account (owner, or admins for roles other than `Admin`)
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        self.access.grant_role(account_id, role);
+    }
+
+    /// Revoke `role` from an account (owner, or admins for roles other than `Admin`)
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        self.access.revoke_role(account_id, role);
+    }
+
+    /// Hand the contract over to a new owner (only owner)
+    pub fn transfer_ownership(&mut self, new_owner_id: AccountId) {
+        self.access.transfer_ownership(new_owner_id);
+    }
+
+    /// Pause `subsystem`, or the whole contract when `None` (only pausers)
+    pub fn pause(&mut self, subsystem: Option<Subsystem>) {
+        self.access.assert_role(Role::Pauser);
+        self.pause.pause(subsystem);
+    }
+
+    /// Lift the pause of `subsystem`, or the global pause when `None` (only pausers)
+    pub fn unpause(&mut self, subsystem: Option<Subsystem>) {
+        self.access.assert_role(Role::Pauser);
+        self.pause.unpause(subsystem);
+    }
+
+    /// NEP-145: add storage deposit for `account_id`, the caller by default
+    #[payable]
+    pub fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        self.storage.deposit(account_id, registration_only.unwrap_or(false))
+    }
+
+    /// NEP-145: withdraw unused storage deposit, all of it when `amount` is `None`
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.storage.withdraw(amount)
+    }
+
+    /// NEP-145: close the caller's storage account and refund its deposit. The account
+    /// must not own any records, `force` can't remove them.
+    #[payable]
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.storage.unregister(force.unwrap_or(false))
+    }
+
+    /// Register a new solver with its TEE attestation (only solver managers)
+    ///
+    /// `attestation_report` is a hex-encoded SGX/TDX DCAP quote whose report data
+    /// commits to `public_key`, the key the solver's enclave signs orders with. The
+    /// quote must be signed by a pinned attestation key.
+    pub fn register_solver(
+        &mut self,
+        solver_address: AccountId,
+        min_quote_amount: U128,
+        max_quote_amount: U128,
+        fee_percentage: u32,
+        attestation_report: String,
+        public_key: String,
+    ) {
+        self.access.assert_role(Role::SolverManager);
+        assert!(fee_percentage <= 1000, "Fee percentage too high"); // Max 10%
+
+        // Verify TEE attestation
+        let quote = self.verify_tee_attestation(&attestation_report, &public_key).expect("Invalid TEE attestation");
+        let tee_config = TEEConfig {
+            tee_enclave_id: hex::encode(&quote.measurement),
+            attestation_report,
+            public_key,
+            is_verified: true,
+        };
+
+        let solver_config = SolverConfig {
+            solver_address: solver_address.clone(),
+            min_quote_amount,
+            max_quote_amount,
+            fee_percentage,
+            is_active: true,
+            reputation: 1000, // Initial reputation
+        };
+
+        self.solvers.insert(&solver_address, &solver_config);
+        self.tee_configs.insert(&solver_address, &tee_config);
+        self.solver_reputation.insert(&solver_address, &1000);
+
+        Event::SolverRegistered { solver_id: solver_address }.emit();
+    }
+
+    /// Request a quote for cross-chain swap
+    ///
+    /// Solvers can bid until `bidding_period` (nanoseconds) has passed, after which
+    /// anyone can settle the request with `settle_quote`. With an `auction` curve,
+    /// bids must meet the curve price at the time they are placed. With
+    /// `partial_fill`, the resulting order can be filled in chunks by several solvers,
+    /// each fill locked under one of the committed secrets. Otherwise the order is
+    /// locked under `hashlock`, the hex-encoded sha256 of a secret only the user knows.
+    /// `to_token` must be wNEAR: solvers lock the output as native NEAR. Only orders
+    /// from a bridged ERC-20 to `wrap.near` have a Fusion order on Ethereum.
+    pub fn request_quote(
+        &mut self,
+        from_token: AccountId,
+        to_token: AccountId,
+        from_amount: U128,
+        deadline: U64,
+        bidding_period: Option<U64>,
+        auction: Option<DutchAuctionCurve>,
+        partial_fill: Option<PartialFillConfig>,
+        hashlock: Option<String>,
+    ) -> String {
+        self.pause.assert_not_paused(Subsystem::Quoting);
+        assert!(from_amount.0 > 0, "Invalid amount");
+        assert!(deadline.0 > env::block_timestamp(), "Invalid deadline");
+        assert!(WNEAR_TOKENS.contains(&to_token.as_str()), "Output token must be wNEAR");
+        if let Some(curve) = &auction {
+            assert!(curve.start_amount.0 > curve.min_amount.0, "Start amount must be greater than min amount");
+            assert!(curve.step_time.0 > 0, "Step time must be greater than 0");
+        }
+        if let Some(config) = &partial_fill {
+            assert!(config.parts >= 2, "Partial fills need at least 2 parts");
+            assert!(from_amount.0 >= config.parts as u128, "Amount too small for parts");
+            assert!(Self::parse_hash(&config.secrets_merkle_root).is_some(), "Invalid merkle root");
+            assert!(hashlock.is_none(), "Partially fillable orders are locked under their merkle secrets");
+        } else {
+            let hashlock = hashlock.as_deref().expect("Missing hashlock");
+            assert!(Self::parse_hash(hashlock).is_some(), "Invalid hashlock");
+        }
+
+        let initial_storage = env::storage_usage();
+        let bidding_end = env::block_timestamp() + bidding_period.map_or(DEFAULT_BIDDING_PERIOD, |period| period.0);
+        assert!(bidding_end < deadline.0, "Bidding period exceeds deadline");
+
+        let request_id = format!("quote_{}", self.quote_counter);
+        self.quote_counter += 1;
+
+        let quote_request = QuoteRequest {
+            request_id: request_id.clone(),
+            requester: env::predecessor_account_id(),
+            from_token,
+            to_token,
+            from_amount,
+            to_amount: U128(0), // Will be set by the winning solver
+            deadline,
+            created_at: U64(env::block_timestamp()),
+            bidding_end: U64(bidding_end),
+            auction,
+            partial_fill,
+            hashlock,
+            intent_id: String::new(),
+            is_executed: false,
+        };
+
+        self.quote_requests.insert(&request_id, &quote_request);
+        self.storage.settle(&quote_request.requester, initial_storage);
+
+        // Emit event for solvers to listen
+        Event::QuoteRequested {
+            request_id: request_id.clone(),
+            requester: quote_request.requester,
+            from_token: quote_request.from_token.to_string(),
+            to_token: quote_request.to_token.to_string(),
+            from_amount,
+            deadline,
+        }
+        .emit();
+
+        request_id
+    }
+
+    /// Submit or replace a bid on an open quote request (called by solver)
+    ///
+    /// Returns the bid amount net of the solver fee.
+    pub fn generate_quote(
+        &mut self,
+        request_id: String,
+        to_amount: U128,
+        intent_id: String,
+        signature: ChainSignature,
+    ) -> U128 {
+        self.pause.assert_not_paused(Subsystem::Quoting);
+        let solver_id = env::predecessor_account_id();
+
+        // Verify solver is registered and active
+        let solver_config = self.solvers.get(&solver_id).expect("Solver not registered");
+        assert!(solver_config.is_active, "Solver not active");
+
+        // Verify TEE configuration
+        let tee_config = self.tee_configs.get(&solver_id).expect("TEE config not found");
+        assert!(tee_config.is_verified, "TEE not verified");
+        assert!(self.tee_measurements.contains(&tee_config.tee_enclave_id), "TEE measurement not allowed");
+        assert_eq!(signature.public_key, tee_config.public_key, "Signature key does not match TEE key");
+
+        let quote_request = self.quote_requests.get(&request_id).expect("Request not found");
+        assert!(env::block_timestamp() < quote_request.bidding_end.0, "Bidding closed");
+        assert!(!quote_request.is_executed, "Request already executed");
+        assert!(self.intents.get(&intent_id).is_none(), "Intent already exists");
+
+        // Validate quote amount
+        assert!(to_amount.0 >= solver_config.min_quote_amount.0, "Quote too low");
+        assert!(to_amount.0 <= solver_config.max_quote_amount.0, "Quote too high");
+
+        // Calculate fee
+        let fee = (to_amount.0 * solver_config.fee_percentage as u128) / 10000;
+        let final_amount = to_amount.0 - fee;
+
+        // Enforce the Dutch-auction price
+        if let Some(current_rate) = self.get_current_rate(request_id.clone()) {
+            assert!(final_amount >= current_rate.0, "Quote below auction price");
+        }
+
+        // A bid must be executable as signed, or it could win and block the request
+        let expected_message = signature::order_message(&intent_id, quote_request.from_amount.0, final_amount, quote_request.deadline.0);
+        assert!(
+            signature.message == expected_message
+                && signature::verify_signature(&signature.signature, &signature.public_key, &signature.message),
+            "Invalid NEAR signature"
+        );
+
+        let bid = QuoteBid {
+            solver_id: solver_id.clone(),
+            to_amount: U128(final_amount),
+            intent_id,
+            signature,
+            submitted_at: U64(env::block_timestamp()),
+        };
+
+        let initial_storage = env::storage_usage();
+        let mut bids = self.quote_bids.get(&request_id).unwrap_or_default();
+        bids.retain(|existing| existing.solver_id != solver_id);
+        bids.push(bid);
+        self.quote_bids.insert(&request_id, &bids);
+        self.storage.settle(&solver_id, initial_storage);
+
+        Event::QuoteBid {
+            request_id,
+            solver_id,
+            to_amount: U128(final_amount),
+        }
+        .emit();
+
+        U128(final_amount)
+    }
+
+    /// Close bidding and turn the best bid into a meta-order
+    ///
+    /// The highest net `to_amount` wins; ties go to the solver with the higher
+    /// reputation, then to the earlier bid. Bids are skipped if their solver could no
+    /// longer bid (deactivated, or its TEE measurement was removed from the allowlist),
+    /// or if their intent id was taken by another order since they were placed. The bids are dropped, crediting their
+    /// solvers, and the requester is charged for the order and intent.
+    pub fn settle_quote(&mut self, request_id: String) -> String {
+        self.pause.assert_not_paused(Subsystem::Quoting);
+        let mut quote_request = self.quote_requests.get(&request_id).expect("Request not found");
+        assert!(!quote_request.is_executed, "Request already executed");
+        assert!(env::block_timestamp() >= quote_request.bidding_end.0, "Bidding still open");
+        assert!(env::block_timestamp() <= quote_request.deadline.0, "Quote expired");
+
+        let bids = self.quote_bids.get(&request_id).unwrap_or_default();
+        let winner = bids
+            .iter()
+            .filter(|bid| self.can_bid(&bid.solver_id))
+            .filter(|bid| self.intents.get(&bid.intent_id).is_none())
+            .max_by(|a, b| {
+                a.to_amount
+                    .0
+                    .cmp(&b.to_amount.0)
+                    .then_with(|| self.get_solver_reputation(a.solver_id.clone()).cmp(&self.get_solver_reputation(b.solver_id.clone())))
+                    .then_with(|| b.submitted_at.0.cmp(&a.submitted_at.0))
+            })
+            .cloned()
+            .expect("No valid bids");
+        self.remove_bids(&request_id);
+        let initial_storage = env::storage_usage();
+
+        // Create meta-order
+        let order_id = format!("order_{}", self.order_counter);
+        self.order_counter += 1;
+
+        let meta_order = MetaOrder {
+            order_id: order_id.clone(),
+            from_token: quote_request.from_token.clone(),
+            to_token: quote_request.to_token.clone(),
+            from_amount: quote_request.from_amount,
+            to_amount: winner.to_amount,
+            deadline: quote_request.deadline,
+            intent_id: winner.intent_id.clone(),
+            signature: winner.signature,
+            solver_id: winner.solver_id.clone(),
+            partial_fill: quote_request.partial_fill.clone(),
+            hashlock: quote_request.hashlock.clone(),
+            filled_amount: U128(0),
+            remaining_amount: quote_request.from_amount,
+            is_executed: false,
+        };
+
+        self.meta_orders.insert(&order_id, &meta_order);
+
+        // Update quote request
+        quote_request.to_amount = winner.to_amount;
+        quote_request.intent_id = winner.intent_id.clone();
+        quote_request.is_executed = true;
+        self.quote_requests.insert(&request_id, &quote_request);
+
+        // Create intent
+        let intent = Intent {
+            intent_id: winner.intent_id.clone(),
+            user_id: quote_request.requester.clone(),
+            from_token: quote_request.from_token.clone(),
+            to_token: quote_request.to_token.clone(),
+            from_amount: quote_request.from_amount,
+            to_amount: winner.to_amount,
+            deadline: quote_request.deadline,
+            status: "pending".to_string(),
+        };
+
+        self.intents.insert(&winner.intent_id, &intent);
+        let mut user_intents = self.user_intents.get(&intent.user_id).unwrap_or_default();
+        user_intents.push(winner.intent_id.clone());
+        self.user_intents.insert(&intent.user_id, &user_intents);
+        self.storage.settle(&quote_request.requester, initial_storage);
+
+        Event::QuoteGenerated {
+            request_id,
+            order_id: order_id.clone(),
+            solver_id: winner.solver_id,
+            to_amount: winner.to_amount,
+        }
+        .emit();
+
+        order_id
+    }
+
+    /// Remove a quote request past its deadline, whether or not it was settled
+    ///
+    /// Anyone can call this; the storage of the request and any bids left is credited
+    /// back to the requester and the bidding solvers. A settled request lives on in
+    /// its meta-order and intent.
+    pub fn remove_expired_quote(&mut self, request_id: String) {
+        let quote_request = self.quote_requests.get(&request_id).expect("Request not found");
+        assert!(env::block_timestamp() > quote_request.deadline.0, "Quote not expired");
+        self.remove_bids(&request_id);
+
+        let initial_storage = env::storage_usage();
+        self.quote_requests.remove(&request_id);
+        self.storage.settle(&quote_request.requester, initial_storage);
+
+        Event::QuoteRemoved { request_id }.emit();
+    }
+
+    /// Remove a meta-order and its intent once executed, failed or past its deadline
+    ///
+    /// Anyone can call this; the storage of the order and intent is credited back to
+    /// the requester. Orders with an HTLC lock in flight can't be removed.
+    pub fn remove_meta_order(&mut self, order_id: String) {
+        let meta_order = self.meta_orders.get(&order_id).expect("Order not found");
+        let intent = self.intents.get(&meta_order.intent_id).expect("Intent not found");
+        assert_ne!(intent.status, "executing", "Order is executing");
+        let finished = meta_order.is_executed || intent.status == "failed" || env::block_timestamp() > meta_order.deadline.0;
+        assert!(finished, "Order not finished");
+
+        // Fills are recorded in callbacks at the contract's expense
+        self.order_fills.remove(&order_id);
+
+        let initial_storage = env::storage_usage();
+        self.meta_orders.remove(&order_id);
+        self.intents.remove(&meta_order.intent_id);
+        let mut user_intents = self.user_intents.get(&intent.user_id).unwrap_or_default();
+        user_intents.retain(|intent_id| *intent_id != meta_order.intent_id);
+        if user_intents.is_empty() {
+            self.user_intents.remove(&intent.user_id);
+        } else {
+            self.user_intents.insert(&intent.user_id, &user_intents);
+        }
+        self.storage.settle(&intent.user_id, initial_storage);
+
+        Event::MetaOrderRemoved { order_id }.emit();
+    }
+
+    /// Execute meta-order using NEAR Chain Signatures
+    ///
+    /// The solver attaches the order's `to_amount` in NEAR, which is locked in the
+    /// HTLC contract under the hashlock the user committed to in `request_quote`. The
+    /// preimage is never passed here; the user reveals it once the counterpart lock
+    /// exists. The order is settled in `on_meta_order_executed`. The solver pays for the
+    /// escrow's storage and needs a NEP-145 deposit on the HTLC contract.
+    #[payable]
+    pub fn execute_meta_order(&mut self, order_id: String) -> Promise {
+        self.pause.assert_not_paused(Subsystem::Execution);
+        let meta_order = self.meta_orders.get(&order_id).expect("Order not found");
+        assert!(env::block_timestamp() <= meta_order.deadline.0, "Order expired");
+        assert!(!meta_order.is_executed, "Order already executed");
+        assert!(meta_order.partial_fill.is_none(), "Order is partially fillable, use fill_meta_order");
+        assert_eq!(env::predecessor_account_id(), meta_order.solver_id, "Only winning solver can execute");
+        assert_eq!(env::attached_deposit().as_yoctonear(), meta_order.to_amount.0, "Attached deposit must match order amount");
+        let hashlock = meta_order.hashlock.as_deref().and_then(Self::parse_hash).expect("Invalid hashlock");
+
+        // Verify NEAR Chain Signature
+        assert!(self.verify_near_signature(&meta_order.signature, &meta_order), "Invalid NEAR signature");
+
+        // Block concurrent executions while the HTLC call is in flight
+        let mut intent = self.intents.get(&meta_order.intent_id).expect("Intent not found");
+        assert_eq!(intent.status, "pending", "Intent not pending");
+        intent.status = "executing".to_string();
+        self.intents.insert(&meta_order.intent_id, &intent);
+
+        // Execute cross-chain swap using HTLC
+        let solver_id = env::predecessor_account_id();
+        self.execute_cross_chain_swap(&meta_order, &intent, &hashlock, meta_order.to_amount, &solver_id)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_EXECUTE_CALLBACK)
+                    .on_meta_order_executed(order_id, solver_id),
+            )
+    }
+
+    /// Fill `parts` chunks of a partially fillable meta-order (called by any active solver)
+    ///
+    /// The fill is locked under the hex-encoded `secret_hash` at `secret_index`, proven
+    /// against the order's merkle root. Index `k - 1` is used when the fill brings the
+    /// order to `k` of `N` parts, and index `N` for the fill that completes the order.
+    /// The secret itself is only revealed by the user once the fill is locked. The
+    /// solver attaches the proportional share of `to_amount` in NEAR and, as with
+    /// `execute_meta_order`, pays for the escrow's storage on the HTLC contract.
+    #[payable]
+    pub fn fill_meta_order(
+        &mut self,
+        order_id: String,
+        parts: u32,
+        secret_index: u32,
+        secret_hash: String,
+        proof: Vec<String>,
+    ) -> Promise {
+        self.pause.assert_not_paused(Subsystem::Execution);
+        let meta_order = self.meta_orders.get(&order_id).expect("Order not found");
+        assert!(env::block_timestamp() <= meta_order.deadline.0, "Order expired");
+        assert!(!meta_order.is_executed, "Order already executed");
+        let config = meta_order.partial_fill.clone().expect("Order is not partially fillable");
+
+        let solver_id = env::predecessor_account_id();
+        let solver_config = self.solvers.get(&solver_id).expect("Solver not registered");
+        assert!(solver_config.is_active, "Solver not active");
+
+        // Work out the making amount and the secret this fill must reveal
+        let total = meta_order.from_amount.0;
+        let part_size = total / config.parts as u128;
+        let filled_parts = (meta_order.filled_amount.0 / part_size) as u32;
+        assert!(parts > 0 && filled_parts + parts <= config.parts, "Invalid number of parts");
+
+        let filled_parts_after = filled_parts + parts;
+        let (filled_after, expected_index) = if filled_parts_after == config.parts {
+            (total, config.parts)
+        } else {
+            (part_size * filled_parts_after as u128, filled_parts_after - 1)
+        };
+        assert_eq!(secret_index, expected_index, "Wrong secret index");
+
+        // Verify the secret hash against the merkle root
+        let secret_hash = Self::parse_hash(&secret_hash).expect("Invalid secret hash");
+        let root = Self::parse_hash(&config.secrets_merkle_root).expect("Invalid merkle root");
+        let proof: Vec<[u8; 32]> = proof.iter().map(|node| Self::parse_hash(node).expect("Invalid proof")).collect();
+        assert!(merkle::verify_proof(&root, merkle::leaf(secret_index as u64, &secret_hash), &proof), "Invalid merkle proof");
+
+        // Proportional share of the taking amount
+        let taking_before = Self::proportional(meta_order.to_amount.0, meta_order.filled_amount.0, total);
+        let taking_after = Self::proportional(meta_order.to_amount.0, filled_after, total);
+        let taking_amount = taking_after - taking_before;
+        // Rounds to zero when `to_amount` is small next to `parts`; filling more parts at once still works
+        assert!(taking_amount > 0, "Fill amount too small");
+        assert_eq!(env::attached_deposit().as_yoctonear(), taking_amount, "Attached deposit must match fill amount");
+
+        // Verify NEAR Chain Signature
+        assert!(self.verify_near_signature(&meta_order.signature, &meta_order), "Invalid NEAR signature");
+
+        // Block concurrent fills while the HTLC call is in flight
+        let mut intent = self.intents.get(&meta_order.intent_id).expect("Intent not found");
+        assert_eq!(intent.status, "pending", "Intent not pending");
+        intent.status = "executing".to_string();
+        self.intents.insert(&meta_order.intent_id, &intent);
+
+        let fill = MetaOrderFill {
+            solver_id: solver_id.clone(),
+            making_amount: U128(filled_after - meta_order.filled_amount.0),
+            taking_amount: U128(taking_amount),
+            filled_amount: U128(filled_after),
+            secret_index,
+            hashlock: hex::encode(secret_hash),
+            timestamp: U64(env::block_timestamp()),
+        };
+
+        self.execute_cross_chain_swap(&meta_order, &intent, &secret_hash, U128(taking_amount), &solver_id)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_EXECUTE_CALLBACK)
+                    .on_meta_order_filled(order_id, fill),
+            )
+    }
+
+    /// Record a partial fill once its HTLC lock has been created
+    #[private]
+    pub fn on_meta_order_filled(&mut self, order_id: String, fill: MetaOrderFill) -> bool {
+        let success = matches!(env::promise_result(0), PromiseResult::Successful(_));
+
+        let mut meta_order = self.meta_orders.get(&order_id).expect("Order not found");
+        let mut intent = self.intents.get(&meta_order.intent_id).expect("Intent not found");
+
+        if success {
+            meta_order.filled_amount = fill.filled_amount;
+            meta_order.remaining_amount = U128(meta_order.from_amount.0 - fill.filled_amount.0);
+            meta_order.is_executed = meta_order.remaining_amount.0 == 0;
+            self.meta_orders.insert(&order_id, &meta_order);
+
+            let mut fills = self.order_fills.get(&order_id).unwrap_or_default();
+            fills.push(fill.clone());
+            self.order_fills.insert(&order_id, &fills);
+
+            intent.status = if meta_order.is_executed { "executed" } else { "pending" }.to_string();
+        } else {
+            // Other solvers can still fill the order
+            intent.status = "pending".to_string();
+
+            // The failed lock returned the deposit to this contract
+            Promise::new(fill.solver_id.clone()).transfer(NearToken::from_yoctonear(fill.taking_amount.0));
+        }
+        self.intents.insert(&meta_order.intent_id, &intent);
+
+        // Update solver reputation
+        self.update_solver_reputation(&fill.solver_id, success);
+
+        Event::MetaOrderFilled {
+            order_id,
+            solver_id: fill.solver_id,
+            making_amount: fill.making_amount,
+            success,
+        }
+        .emit();
+
+        success
+    }
+
+    /// Settle a meta-order once the HTLC lock has been created
+    #[private]
+    pub fn on_meta_order_executed(&mut self, order_id: String, solver_id: AccountId) -> bool {
+        let success = matches!(env::promise_result(0), PromiseResult::Successful(_));
+
+        let mut meta_order = self.meta_orders.get(&order_id).expect("Order not found");
+        let mut intent = self.intents.get(&meta_order.intent_id).expect("Intent not found");
+
+        if success {
+            // Mark order as executed
+            meta_order.is_executed = true;
+            meta_order.filled_amount = meta_order.from_amount;
+            meta_order.remaining_amount = U128(0);
+            self.meta_orders.insert(&order_id, &meta_order);
+
+            intent.status = "executed".to_string();
+        } else {
+            intent.status = "failed".to_string();
+
+            // The failed lock returned the deposit to this contract
+            Promise::new(solver_id.clone()).transfer(NearToken::from_yoctonear(meta_order.to_amount.0));
+        }
+        self.intents.insert(&meta_order.intent_id, &intent);
+
+        // Update solver reputation
+        self.update_solver_reputation(&solver_id, success);
+
+        Event::MetaOrderExecuted {
+            order_id,
+            solver_id,
+            success,
+            hashlock: None,
+        }
+        .emit();
+
+        success
+    }
+
+    /// Whether a solver is active and its verified TEE measurement is still allowed
+    fn can_bid(&self, solver_id: &AccountId) -> bool {
+        let active = self.solvers.get(solver_id).is_some_and(|solver| solver.is_active);
+        let attested = self
+            .tee_configs
+            .get(solver_id)
+            .is_some_and(|tee_config| tee_config.is_verified && self.tee_measurements.contains(&tee_config.tee_enclave_id));
+        active && attested
+    }
+
+    /// Verify the TEE quote's signature by a pinned attestation key, then check its
+    /// measurement against the allowlist and that it binds the enclave key
+    fn verify_tee_attestation(&self, attestation_report: &str, public_key: &str) -> Option<tee::Quote> {
+        let quote = tee::verify_quote(&hex::decode(attestation_report).ok()?)?;
+        let pinned = self.attestation_keys.contains(&hex::encode(quote.attestation_key));
+        let allowed = self.tee_measurements.contains(&hex::encode(&quote.measurement));
+        (pinned && allowed && quote.binds_public_key(public_key)).then_some(quote)
+    }
+
+    /// Verify NEAR Chain Signature and that the signed message commits to the order
+    fn verify_near_signature(&self, signature: &ChainSignature, meta_order: &MetaOrder) -> bool {
+        let expected_message = signature::order_message(
+            &meta_order.intent_id,
+            meta_order.from_amount.0,
+            meta_order.to_amount.0,
+            meta_order.deadline.0,
+        );
+        signature.message == expected_message
+            && signature::verify_signature(&signature.signature, &signature.public_key, &signature.message)
+    }
+
+    /// Execute cross-chain swap using HTLC
+    fn execute_cross_chain_swap(
+        &self,
+        meta_order: &MetaOrder,
+        intent: &Intent,
+        hashlock: &[u8; 32],
+        amount: U128,
+        solver_id: &AccountId,
+    ) -> Promise {
+        // Create HTLC lock for cross-chain execution
+        let lock = LockMsg {
+            hashlock: hex::encode(hashlock),
+            timelock: HTLC_TIMELOCK,
+            exclusive_period: None,
+            dest_chain: "near".to_string(),
+            dest_user: intent.user_id.to_string(),
+            min_return: amount,
+            output_token: meta_order.to_token.to_string(),
+            resolver_id: Some(intent.user_id.clone()),
+        };
+
+        Event::HtlcLockRequested {
+            order_id: meta_order.order_id.clone(),
+            htlc_contract: self.htlc_contract.clone(),
+            hashlock: lock.hashlock.clone(),
+        }
+        .emit();
+
+        ext_htlc::ext(self.htlc_contract.clone())
+            .with_attached_deposit(env::attached_deposit())
+            .with_static_gas(GAS_FOR_CROSS_CONTRACT_CALL)
+            .lock_near(lock, Some(solver_id.clone()))
+    }
+
+    /// "open", "partially_filled", "executing", "executed", "failed" or "expired".
+    /// Only "open" and "partially_filled" orders can still be executed or filled.
+    fn meta_order_status(&self, order: &MetaOrder) -> &'static str {
+        let intent_status = self.intents.get(&order.intent_id).map(|intent| intent.status);
+        if order.is_executed {
+            "executed"
+        } else if intent_status.as_deref() == Some("failed") {
+            "failed"
+        } else if env::block_timestamp() > order.deadline.0 {
+            "expired"
+        } else if intent_status.as_deref() == Some("executing") {
+            "executing"
+        } else if order.filled_amount.0 > 0 {
+            "partially_filled"
+        } else {
+            "open"
+        }
+    }
+
+    /// `amount * filled / total`
+    fn proportional(amount: u128, filled: u128, total: u128) -> u128 {
+        amount.checked_mul(filled).expect("Amount overflow") / total
+    }
+
+    /// Drop the bids on a request one at a time, so each solver gets back what their bid used
+    fn remove_bids(&mut self, request_id: &String) {
+        let mut bids = self.quote_bids.get(request_id).unwrap_or_default();
+        while let Some(bid) = bids.pop() {
+            let initial_storage = env::storage_usage();
+            if bids.is_empty() {
+                self.quote_bids.remove(request_id);
+            } else {
+                self.quote_bids.insert(request_id, &bids);
+            }
+            self.storage.settle(&bid.solver_id, initial_storage);
+        }
+    }
+
+    /// Parse a 32-byte hex hash
+    fn parse_hash(value: &str) -> Option<[u8; 32]> {
+        hex::decode(value).ok()?.try_into().ok()
+    }
+
+    /// Update solver reputation based on performance
+    fn update_solver_reputation(&mut self, solver_address: &AccountId, success: bool) {
+        let current_reputation = self.solver_reputation.get(solver_address).unwrap_or(1000);
+        
+        let new_reputation = if success {
+            current_reputation + 10 // Increase reputation
+        } else {
+            current_reputation.saturating_sub(10)
+        };
+
+        self.solver_reputation.insert(solver_address, &new_reputation);
+    }
+
+    /// Get quote request details
+    pub fn get_quote_request(&self, request_id: String) -> Option<QuoteRequest> {
+        self.quote_requests.get(&request_id)
+    }
+
+    /// Quote requests still open for bids or settlement: not executed and not expired
+    pub fn get_open_quote_requests(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<QuoteRequest> {
+        let now = env::block_timestamp();
+        let open = self.quote_requests.values().filter(|request| !request.is_executed && now <= request.deadline.0);
+        paginate(open, from_index, limit)
+    }
+
+    /// Get the current Dutch-auction price of a quote request, if it has a curve
+    pub fn get_current_rate(&self, request_id: String) -> Option<U128> {
+        let quote_request = self.quote_requests.get(&request_id).expect("Request not found");
+        let elapsed = env::block_timestamp().saturating_sub(quote_request.created_at.0);
+        quote_request.auction.map(|curve| U128(curve.price_at(elapsed)))
+    }
+
+    /// Get the bids submitted for a quote request
+    pub fn get_quote_bids(&self, request_id: String) -> Vec<QuoteBid> {
+        self.quote_bids.get(&request_id).unwrap_or_default()
+    }
+
+    /// Get meta-order details
+    pub fn get_meta_order(&self, order_id: String) -> Option<MetaOrder> {
+        self.meta_orders.get(&order_id)
+    }
+
+    /// Meta-orders, optionally only those with `status` ("open", "partially_filled", "executing",
+    /// "executed", "failed" or "expired")
+    pub fn get_meta_orders(&self, status: Option<String>, from_index: Option<U64>, limit: Option<U64>) -> Vec<MetaOrder> {
+        let orders = self
+            .meta_orders
+            .values()
+            .filter(|order| status.as_deref().is_none_or(|status| self.meta_order_status(order) == status));
+        paginate(orders, from_index, limit)
+    }
+
+    /// Get the 1inch Fusion order a meta-order settles as on Ethereum
+    pub fn get_fusion_order(&self, order_id: String, maker: String, maker_traits: String) -> FusionOrder {
+        let meta_order = self.meta_orders.get(&order_id).expect("Order not found");
+        FusionOrder::from_meta_order(&meta_order, &maker, &maker_traits)
+    }
+
+    /// Get the EIP-712 hash of a meta-order's Fusion order for the Aggregation Router on `chain_id`
+    pub fn get_fusion_order_hash(&self, order_id: String, maker: String, maker_traits: String, chain_id: u64) -> String {
+        let fusion_order = self.get_fusion_order(order_id, maker, maker_traits);
+        let router = fusion::parse_address(fusion::AGGREGATION_ROUTER_V6).expect("Invalid router address");
+        fusion::to_hex(&fusion_order.hash(chain_id, &router))
+    }
+
+    /// Get the fill history of a meta-order
+    pub fn get_order_fills(&self, order_id: String) -> Vec<MetaOrderFill> {
+        self.order_fills.get(&order_id).unwrap_or_default()
+    }
+
+    /// Get solver configuration
+    pub fn get_solver_config(&self, solver_address: AccountId) -> Option<SolverConfig> {
+        self.solvers.get(&solver_address)
+    }
+
+    /// Intents created for `user_id`, oldest first
+    pub fn get_intents_by_user(&self, user_id: AccountId, from_index: Option<U64>, limit: Option<U64>) -> Vec<Intent> {
+        let intent_ids = self.user_intents.get(&user_id).unwrap_or_default();
+        paginate(intent_ids.iter().filter_map(|intent_id| self.intents.get(intent_id)), from_index, limit)
+    }
+
+    /// Get intent details
+    pub fn get_intent(&self, intent_id: String) -> Option<Intent> {
+        self.intents.get(&intent_id)
+    }
+
+    /// Deactivate solver (only solver managers)
+    pub fn deactivate_solver(&mut self, solver_address: AccountId) {
+        self.access.assert_role(Role::SolverManager);
+        
+        if let Some(mut solver_config) = self.solvers.get(&solver_address) {
+            solver_config.is_active = false;
+            self.solvers.insert(&solver_address, &solver_config);
+            Event::SolverDeactivated { solver_id: solver_address }.emit();
+        }
+    }
+
+    /// Update solver configuration (only solver managers)
+    pub fn update_solver_config(
+        &mut self,
+        solver_address: AccountId,
+        min_quote_amount: U128,
+        max_quote_amount: U128,
+        fee_percentage: u32,
+    ) {
+        self.access.assert_role(Role::SolverManager);
+        assert!(fee_percentage <= 1000, "Fee percentage too high");
+
+        if let Some(mut solver_config) = self.solvers.get(&solver_address) {
+            solver_config.min_quote_amount = min_quote_amount;
+            solver_config.max_quote_amount = max_quote_amount;
+            solver_config.fee_percentage = fee_percentage;
+            self.solvers.insert(&solver_address, &solver_config);
+            Event::SolverConfigUpdated { solver_id: solver_address }.emit();
+        }
+    }
+
+    /// Allow an enclave measurement (hex MRENCLAVE or MRTD) (only admins)
+    pub fn add_tee_measurement(&mut self, measurement: String) {
+        self.access.assert_role(Role::Admin);
+        let measurement = measurement.to_lowercase();
+        assert!(hex::decode(&measurement).is_ok_and(|bytes| bytes.len() == 32 || bytes.len() == 48), "Invalid measurement");
+
+        self.tee_measurements.insert(&measurement);
+        Event::TeeMeasurementAdded { measurement }.emit();
+    }
+
+    /// Remove an enclave measurement from the allowlist (only admins)
+    pub fn remove_tee_measurement(&mut self, measurement: String) {
+        self.access.assert_role(Role::Admin);
+
+        let measurement = measurement.to_lowercase();
+        self.tee_measurements.remove(&measurement);
+        Event::TeeMeasurementRemoved { measurement }.emit();
+    }
+
+    /// Pin a quoting enclave attestation key (hex P-256 x || y) (only admins)
+    ///
+    /// The contract doesn't check the PCK certificate chain, so the key must be
+    /// checked against Intel's chain off-chain before it is pinned.
+    pub fn add_attestation_key(&mut self, attestation_key: String) {
+        self.access.assert_role(Role::Admin);
+        let attestation_key = attestation_key.to_lowercase();
+        assert!(hex::decode(&attestation_key).is_ok_and(|bytes| tee::is_valid_attestation_key(&bytes)), "Invalid attestation key");
+
+        self.attestation_keys.insert(&attestation_key);
+        Event::AttestationKeyAdded { attestation_key }.emit();
+    }
+
+    /// Unpin an attestation key (only admins)
+    pub fn remove_attestation_key(&mut self, attestation_key: String) {
+        self.access.assert_role(Role::Admin);
+
+        let attestation_key = attestation_key.to_lowercase();
+        self.attestation_keys.remove(&attestation_key);
+        Event::AttestationKeyRemoved { attestation_key }.emit();
+    }
+
+    /// Current owner
+    pub fn get_owner(&self) -> AccountId {
+        self.access.owner_id.clone()
+    }
+
+    /// Roles granted explicitly to an account
+    pub fn get_roles(&self, account_id: AccountId) -> Vec<Role> {
+        self.access.roles_of(&account_id)
+    }
+
+    /// Whether an account holds `role`, directly or as owner or admin
+    pub fn has_role(&self, account_id: AccountId, role: Role) -> bool {
+        self.access.has_role(&account_id, role)
+    }
+
+    /// Global and per-subsystem pause switches
+    pub fn get_pause_state(&self) -> PauseState {
+        self.pause.clone()
+    }
+
+    /// NEP-145: storage balance of an account, if registered
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage.balance_of(&account_id)
+    }
+
+    /// NEP-145: minimum deposit to register and no maximum
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageLedger::bounds()
+    }
+
+    /// List allowed enclave measurements
+    pub fn get_tee_measurements(&self) -> Vec<String> {
+        self.tee_measurements.to_vec()
+    }
+
+    /// List pinned attestation keys
+    pub fn get_attestation_keys(&self) -> Vec<String> {
+        self.attestation_keys.to_vec()
+    }
+
+    /// Get solver TEE configuration
+    pub fn get_tee_config(&self, solver_address: AccountId) -> Option<TEEConfig> {
+        self.tee_configs.get(&solver_address)
+    }
+
+    /// Get solver reputation
+    pub fn get_solver_reputation(&self, solver_address: AccountId) -> u32 {
+        self.solver_reputation.get(&solver_address).unwrap_or(0)
+    }
+
+    /// List active solvers
+    pub fn get_active_solvers(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<AccountId> {
+        let active_solvers = self.solvers.iter().filter(|(_, config)| config.is_active).map(|(solver_address, _)| solver_address);
+        paginate(active_solvers, from_index, limit)
+    }
+
+    /// Solvers with their current reputation, highest first
+    pub fn get_solvers_by_reputation(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<SolverConfig> {
+        let mut solvers: Vec<SolverConfig> = self
+            .solvers
+            .values()
+            .map(|mut config| {
+                config.reputation = self.get_solver_reputation(config.solver_address.clone());
+                config
+            })
+            .collect();
+        solvers.sort_by(|a, b| b.reputation.cmp(&a.reputation).then_with(|| a.solver_address.cmp(&b.solver_address)));
+        paginate(solvers.into_iter(), from_index, limit)
+    }
+
+    /// Get quote statistics
+    pub fn get_quote_stats(&self) -> (u64, u64) {
+        let total_requests = self.quote_counter;
+        let total_orders = self.order_counter;
+        (total_requests, total_orders)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    /// Seed of the ed25519 key every test solver signs with
+    const SOLVER_SEED: [u8; 32] = [6u8; 32];
+
+    /// MRENCLAVE of `tee::tests::sample_sgx_quote_for`
+    const SGX_MRENCLAVE: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+
+    /// Secret the test user locks their orders under
+    const USER_SECRET: [u8; 32] = [7u8; 32];
+
+    fn hashlock() -> String {
+        hex::encode(env::sha256(&USER_SECRET))
+    }
+
+    fn solver_key() -> String {
+        let public_key = ed25519_dalek::SigningKey::from_bytes(&SOLVER_SEED).verifying_key();
+        format!("ed25519:{}", near_sdk::bs58::encode(public_key.as_bytes()).into_string())
+    }
+
+    fn sign(message: &str) -> String {
+        use ed25519_dalek::Signer;
+        hex::encode(ed25519_dalek::SigningKey::from_bytes(&SOLVER_SEED).sign(message.as_bytes()).to_bytes())
+    }
+
+    fn owner() -> AccountId {
+        "owner.testnet".parse().unwrap()
+    }
+
+    fn solver() -> AccountId {
+        "solver.testnet".parse().unwrap()
+    }
+
+    fn setup() -> ShadeAgentSolver {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(owner());
+        testing_env!(context.build());
+        let mut contract = ShadeAgentSolver::new(owner(), "htlc.testnet".parse().unwrap());
+        contract.add_attestation_key(tee::tests::attestation_key());
+        contract
+    }
+
+    fn register(contract: &mut ShadeAgentSolver, quote: &[u8], public_key: &str) {
+        contract.register_solver(solver(), U128(1), U128(1_000_000), 100, hex::encode(quote), public_key.to_string());
+    }
+
+    fn set_context(predecessor: &AccountId, timestamp: u64) {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(predecessor.clone()).block_timestamp(timestamp);
+        testing_env!(context.build());
+    }
+
+    /// Deposits 0.1 NEAR of storage for `account_id`
+    fn deposit_storage(contract: &mut ShadeAgentSolver, account_id: &AccountId) {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(account_id.clone()).attached_deposit(NearToken::from_millinear(100));
+        testing_env!(context.build());
+        contract.storage_deposit(None, None);
+    }
+
+    /// Registers `solver_0.testnet` .. `solver_{n-1}.testnet` with the given fees and
+    /// storage, and opens a quote request with a 100ns bidding window
+    fn setup_auction(fees: &[u32]) -> (ShadeAgentSolver, Vec<AccountId>, String) {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        let solvers: Vec<AccountId> = (0..fees.len()).map(|i| format!("solver_{}.testnet", i).parse().unwrap()).collect();
+        for (solver_id, fee) in solvers.iter().zip(fees) {
+            let quote = tee::tests::sample_sgx_quote_for(&solver_key());
+            contract.register_solver(solver_id.clone(), U128(1), U128(1_000_000), *fee, hex::encode(quote), solver_key());
+        }
+        for solver_id in &solvers {
+            deposit_storage(&mut contract, solver_id);
+        }
+
+        let user: AccountId = "user.testnet".parse().unwrap();
+        deposit_storage(&mut contract, &user);
+        set_context(&user, 0);
+        let request_id = contract.request_quote(
+            "usdc.testnet".parse().unwrap(),
+            "wrap.testnet".parse().unwrap(),
+            U128(1_000),
+            U64(1_000),
+            Some(U64(100)),
+            None,
+            None,
+            Some(hashlock()),
+        );
+        (contract, solvers, request_id)
+    }
+
+    /// Signs the order the bid turns into, net of the solver's fee
+    fn signed_bid(contract: &ShadeAgentSolver, solver_id: &AccountId, request_id: &str, to_amount: u128) -> ChainSignature {
+        let request = contract.get_quote_request(request_id.to_string()).unwrap();
+        let fee = contract.get_solver_config(solver_id.clone()).unwrap().fee_percentage as u128;
+        let message = signature::order_message(&format!("intent_{}", solver_id), request.from_amount.0, to_amount - to_amount * fee / 10000, request.deadline.0);
+        ChainSignature {
+            signature: sign(&message),
+            public_key: solver_key(),
+            message,
+        }
+    }
+
+    fn bid(contract: &mut ShadeAgentSolver, solver_id: &AccountId, request_id: &str, to_amount: u128, timestamp: u64) -> U128 {
+        set_context(solver_id, timestamp);
+        let signature = signed_bid(contract, solver_id, request_id, to_amount);
+        contract.generate_quote(request_id.to_string(), U128(to_amount), format!("intent_{}", solver_id), signature)
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid NEAR signature")]
+    fn bid_rejects_junk_signature() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        set_context(&solvers[0], 10);
+        let mut signature = signed_bid(&contract, &solvers[0], &request_id, 10_000);
+        signature.signature = hex::encode([1u8; 64]);
+        contract.generate_quote(request_id, U128(10_000), format!("intent_{}", solvers[0]), signature);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid NEAR signature")]
+    fn bid_signature_must_cover_net_amount() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        set_context(&solvers[0], 10);
+        let signature = signed_bid(&contract, &solvers[0], &request_id, 20_000);
+        contract.generate_quote(request_id, U128(10_000), format!("intent_{}", solvers[0]), signature);
+    }
+
+    #[test]
+    fn register_solver_with_allowed_measurement() {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        register(&mut contract, &tee::tests::sample_sgx_quote_for(&solver_key()), &solver_key());
+
+        let tee_config = contract.get_tee_config(solver()).unwrap();
+        assert!(tee_config.is_verified);
+        assert_eq!(tee_config.tee_enclave_id, SGX_MRENCLAVE);
+        assert_eq!(tee_config.public_key, solver_key());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid TEE attestation")]
+    fn register_solver_rejects_unknown_measurement() {
+        let mut contract = setup();
+        register(&mut contract, &tee::tests::sample_sgx_quote_for(&solver_key()), &solver_key());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid TEE attestation")]
+    fn register_solver_rejects_unbound_key() {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        let other_key = "ed25519:DcA2MzgpJbrUATQLLceocVckhhAqrkingax4oJ9kZ847";
+        register(&mut contract, &tee::tests::sample_sgx_quote_for(other_key), &solver_key());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid TEE attestation")]
+    fn register_solver_rejects_unpinned_attestation_key() {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        contract.remove_attestation_key(tee::tests::attestation_key().to_uppercase());
+        assert!(contract.get_attestation_keys().is_empty());
+        register(&mut contract, &tee::tests::sample_sgx_quote_for(&solver_key()), &solver_key());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid TEE attestation")]
+    fn register_solver_rejects_tampered_quote() {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        let mut quote = tee::tests::sample_sgx_quote_for(&solver_key());
+        quote[2] = 3; // Header is covered by the signature
+        register(&mut contract, &quote, &solver_key());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid attestation key")]
+    fn attestation_key_must_be_a_curve_point() {
+        let mut contract = setup();
+        contract.add_attestation_key(hex::encode([1u8; 64]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Admin")]
+    fn only_admins_pin_attestation_keys() {
+        let mut contract = setup();
+        set_context(&solver(), 0);
+        contract.add_attestation_key(tee::tests::attestation_key());
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Admin")]
+    fn only_admins_manage_measurements() {
+        let mut contract = setup();
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(solver());
+        testing_env!(context.build());
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+    }
+
+    #[test]
+    fn admin_removes_measurements() {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        contract.remove_tee_measurement(SGX_MRENCLAVE.to_uppercase());
+        assert!(contract.get_tee_measurements().is_empty());
+        assert_eq!(
+            near_sdk::test_utils::get_logs().last().unwrap(),
+            &Event::TeeMeasurementRemoved { measurement: SGX_MRENCLAVE.to_string() }.to_log()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Admin")]
+    fn only_admins_remove_measurements() {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        set_context(&solver(), 0);
+        contract.remove_tee_measurement(SGX_MRENCLAVE.to_string());
+    }
+
+    #[test]
+    fn solver_manager_registers_solvers() {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        let manager: AccountId = "manager.testnet".parse().unwrap();
+        contract.grant_role(manager.clone(), Role::SolverManager);
+        assert_eq!(contract.get_roles(manager.clone()), vec![Role::SolverManager]);
+
+        set_context(&manager, 0);
+        register(&mut contract, &tee::tests::sample_sgx_quote_for(&solver_key()), &solver_key());
+        contract.deactivate_solver(solver());
+        assert!(!contract.get_solver_config(solver()).unwrap().is_active);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role SolverManager")]
+    fn only_solver_managers_register_solvers() {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        set_context(&solver(), 0);
+        register(&mut contract, &tee::tests::sample_sgx_quote_for(&solver_key()), &solver_key());
+    }
+
+    #[test]
+    fn solver_manager_updates_solver_config() {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        register(&mut contract, &tee::tests::sample_sgx_quote_for(&solver_key()), &solver_key());
+        let manager: AccountId = "manager.testnet".parse().unwrap();
+        contract.grant_role(manager.clone(), Role::SolverManager);
+
+        set_context(&manager, 0);
+        contract.update_solver_config(solver(), U128(10), U128(5_000), 250);
+        let config = contract.get_solver_config(solver()).unwrap();
+        assert_eq!(config.min_quote_amount, U128(10));
+        assert_eq!(config.max_quote_amount, U128(5_000));
+        assert_eq!(config.fee_percentage, 250);
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::SolverConfigUpdated { solver_id: solver() }.to_log()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role SolverManager")]
+    fn only_solver_managers_update_solver_config() {
+        let mut contract = setup();
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+        register(&mut contract, &tee::tests::sample_sgx_quote_for(&solver_key()), &solver_key());
+        set_context(&solver(), 0);
+        contract.update_solver_config(solver(), U128(10), U128(5_000), 250);
+    }
+
+    #[test]
+    fn admin_revokes_roles() {
+        let mut contract = setup();
+        let manager: AccountId = "manager.testnet".parse().unwrap();
+        contract.grant_role(manager.clone(), Role::SolverManager);
+        contract.revoke_role(manager.clone(), Role::SolverManager);
+        assert!(contract.get_roles(manager.clone()).is_empty());
+        assert!(!contract.has_role(manager.clone(), Role::SolverManager));
+        assert_eq!(
+            near_sdk::test_utils::get_logs().last().unwrap(),
+            &Event::RoleRevoked { account_id: manager, role: Role::SolverManager, by: owner() }.to_log()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot manage role SolverManager")]
+    fn only_admins_revoke_roles() {
+        let mut contract = setup();
+        let manager: AccountId = "manager.testnet".parse().unwrap();
+        contract.grant_role(manager.clone(), Role::SolverManager);
+        set_context(&solver(), 0);
+        contract.revoke_role(manager, Role::SolverManager);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Admin")]
+    fn ownership_transfer_moves_admin_rights() {
+        let mut contract = setup();
+        contract.transfer_ownership(solver());
+        assert_eq!(contract.get_owner(), solver());
+        contract.add_tee_measurement(SGX_MRENCLAVE.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Quoting paused")]
+    fn paused_quoting_rejects_bids() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        set_context(&owner(), 0);
+        contract.pause(Some(Subsystem::Quoting));
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Execution paused")]
+    fn global_pause_halts_execution() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 10);
+        set_context(&owner(), 100);
+        let order_id = contract.settle_quote(request_id);
+        contract.pause(None);
+
+        set_context(&solvers[0], 200);
+        contract.execute_meta_order(order_id);
+    }
+
+    #[test]
+    fn pauser_unpauses_subsystems() {
+        let mut contract = setup();
+        let pauser: AccountId = "pauser.testnet".parse().unwrap();
+        contract.grant_role(pauser.clone(), Role::Pauser);
+
+        set_context(&pauser, 0);
+        contract.pause(None);
+        contract.pause(Some(Subsystem::Quoting));
+        contract.unpause(None);
+        contract.unpause(Some(Subsystem::Quoting));
+        let pause = contract.get_pause_state();
+        assert!(!pause.global);
+        assert!(pause.subsystems.is_empty());
+        assert_eq!(
+            near_sdk::test_utils::get_logs().last().unwrap(),
+            &Event::Unpaused { subsystem: Some(Subsystem::Quoting), by: pauser }.to_log()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role Pauser")]
+    fn only_pausers_unpause() {
+        let mut contract = setup();
+        contract.pause(None);
+        set_context(&solver(), 0);
+        contract.unpause(None);
+    }
+
+    #[test]
+    fn storage_withdraw_returns_available_balance() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &solver());
+        let balance = contract.storage_balance_of(solver()).unwrap();
+
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(solver()).attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let remaining = contract.storage_withdraw(None);
+        assert_eq!(remaining.available, U128(0));
+        assert_eq!(remaining.total.0, balance.total.0 - balance.available.0);
+
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, solver());
+        assert!(matches!(
+            receipts[0].actions[..],
+            [near_sdk::mock::MockAction::Transfer { deposit, .. }] if deposit.as_yoctonear() == balance.available.0
+        ));
+    }
+
+    #[test]
+    fn storage_unregister_refunds_deposit() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &solver());
+
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(solver()).attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        assert!(contract.storage_unregister(None));
+        assert!(contract.storage_balance_of(solver()).is_none());
+
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, solver());
+        assert!(matches!(
+            receipts[0].actions[..],
+            [near_sdk::mock::MockAction::Transfer { deposit, .. }] if deposit == NearToken::from_millinear(100)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid hashlock")]
+    fn request_quote_takes_a_32_byte_hashlock() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &"user.testnet".parse().unwrap());
+        set_context(&"user.testnet".parse().unwrap(), 0);
+        contract.request_quote("usdc.testnet".parse().unwrap(), "wrap.testnet".parse().unwrap(), U128(1_000), U64(1_000), Some(U64(100)), None, None, Some(hex::encode([7u8; 16])));
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing hashlock")]
+    fn request_quote_requires_hashlock() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &"user.testnet".parse().unwrap());
+        set_context(&"user.testnet".parse().unwrap(), 0);
+        contract.request_quote("usdc.testnet".parse().unwrap(), "wrap.testnet".parse().unwrap(), U128(1_000), U64(1_000), Some(U64(100)), None, None, None);
+    }
+
+    #[test]
+    fn paginated_views_track_quote_lifecycle() {
+        let (mut contract, solvers, request_id) = setup_auction(&[1000, 100]);
+        let user: AccountId = "user.testnet".parse().unwrap();
+        assert_eq!(contract.get_open_quote_requests(None, None).len(), 1);
+        assert_eq!(contract.get_active_solvers(None, Some(U64(1))).len(), 1);
+
+        bid(&mut contract, &solvers[1], &request_id, 9_500, 20);
+        set_context(&owner(), 100);
+        let order_id = contract.settle_quote(request_id);
+
+        assert!(contract.get_open_quote_requests(None, None).is_empty());
+        let open_orders = contract.get_meta_orders(Some("open".to_string()), None, None);
+        assert_eq!(open_orders.len(), 1);
+        assert_eq!(open_orders[0].order_id, order_id);
+        assert!(contract.get_meta_orders(Some("executed".to_string()), None, None).is_empty());
+
+        set_context(&owner(), 1_001);
+        assert!(contract.get_meta_orders(Some("open".to_string()), None, None).is_empty());
+        assert_eq!(contract.get_meta_orders(Some("expired".to_string()), None, None)[0].order_id, order_id);
+
+        let intents = contract.get_intents_by_user(user, None, None);
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].intent_id, format!("intent_{}", solvers[1]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Account not registered for storage")]
+    fn request_quote_requires_storage_deposit() {
+        let mut contract = setup();
+        set_context(&"user.testnet".parse().unwrap(), 0);
+        contract.request_quote("usdc.testnet".parse().unwrap(), "wrap.testnet".parse().unwrap(), U128(1_000), U64(1_000), Some(U64(100)), None, None, Some(hashlock()));
+    }
+
+    #[test]
+    fn expired_quote_removal_credits_storage() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100, 100]);
+        let user: AccountId = "user.testnet".parse().unwrap();
+        let deposited = contract.storage_balance_of(solvers[0].clone()).unwrap().available;
+        let user_balance = contract.storage_balance_of(user.clone()).unwrap();
+        assert!(user_balance.available.0 < user_balance.total.0 - StorageLedger::bounds().min.0);
+
+        bid(&mut contract, &solvers[0], &request_id, 9_500, 10);
+        bid(&mut contract, &solvers[1], &request_id, 9_600, 20);
+        assert!(contract.storage_balance_of(solvers[0].clone()).unwrap().available.0 < deposited.0);
+
+        set_context(&owner(), 1_001);
+        contract.remove_expired_quote(request_id.clone());
+        assert!(contract.get_quote_request(request_id.clone()).is_none());
+        assert!(contract.get_quote_bids(request_id.clone()).is_empty());
+        for solver_id in &solvers {
+            assert_eq!(contract.storage_balance_of(solver_id.clone()).unwrap().available, deposited);
+        }
+        let user_balance = contract.storage_balance_of(user).unwrap();
+        assert_eq!(user_balance.available.0, user_balance.total.0 - StorageLedger::bounds().min.0);
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::QuoteRemoved { request_id }.to_log()]);
+    }
+
+    #[test]
+    fn settle_charges_requester_and_frees_bids() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100, 100]);
+        let user: AccountId = "user.testnet".parse().unwrap();
+        let deposited = contract.storage_balance_of(solvers[0].clone()).unwrap().available;
+        let requested = contract.storage_balance_of(user.clone()).unwrap().available;
+        bid(&mut contract, &solvers[0], &request_id, 9_500, 10);
+        bid(&mut contract, &solvers[1], &request_id, 9_600, 20);
+
+        set_context(&owner(), 100);
+        contract.settle_quote(request_id.clone());
+        assert!(contract.get_quote_bids(request_id.clone()).is_empty());
+        for solver_id in &solvers {
+            assert_eq!(contract.storage_balance_of(solver_id.clone()).unwrap().available, deposited);
+        }
+        let settled = contract.storage_balance_of(user.clone()).unwrap().available;
+        assert!(settled.0 < requested.0);
+
+        set_context(&owner(), 1_001);
+        contract.remove_expired_quote(request_id.clone());
+        assert!(contract.get_quote_request(request_id).is_none());
+        assert!(contract.storage_balance_of(user).unwrap().available.0 > settled.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Quote not expired")]
+    fn open_quote_cannot_be_removed() {
+        let (mut contract, _, request_id) = setup_auction(&[100]);
+        set_context(&owner(), 1_000);
+        contract.remove_expired_quote(request_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Output token must be wNEAR")]
+    fn request_quote_rejects_non_near_output() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &"user.testnet".parse().unwrap());
+        set_context(&"user.testnet".parse().unwrap(), 0);
+        contract.request_quote("wrap.testnet".parse().unwrap(), "usdc.testnet".parse().unwrap(), U128(1_000), U64(1_000), Some(U64(100)), None, None, Some(hashlock()));
+    }
+
+    #[test]
+    fn solvers_sorted_by_reputation() {
+        let (mut contract, solvers, _) = setup_auction(&[100, 100, 100]);
+        contract.solver_reputation.insert(&solvers[2], &1_200);
+        contract.solver_reputation.insert(&solvers[0], &800);
+
+        let ranked: Vec<AccountId> = contract.get_solvers_by_reputation(None, None).into_iter().map(|solver| solver.solver_address).collect();
+        assert_eq!(ranked, vec![solvers[2].clone(), solvers[1].clone(), solvers[0].clone()]);
+
+        let page = contract.get_solvers_by_reputation(Some(U64(1)), Some(U64(1)));
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].solver_address, solvers[1]);
+        assert_eq!(page[0].reputation, 1_000);
+    }
+
+    #[test]
+    fn best_net_bid_wins() {
+        // solver_0 charges 10%, solver_1 charges 1%
+        let (mut contract, solvers, request_id) = setup_auction(&[1000, 100]);
+        assert_eq!(bid(&mut contract, &solvers[0], &request_id, 10_000, 10), U128(9_000));
+        assert_eq!(bid(&mut contract, &solvers[1], &request_id, 9_500, 20), U128(9_405));
+
+        set_context(&owner(), 100);
+        let order_id = contract.settle_quote(request_id.clone());
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![Event::QuoteGenerated {
+                request_id: request_id.clone(),
+                order_id: order_id.clone(),
+                solver_id: solvers[1].clone(),
+                to_amount: U128(9_405),
+            }
+            .to_log()]
+        );
+
+        let meta_order = contract.get_meta_order(order_id).unwrap();
+        assert_eq!(meta_order.solver_id, solvers[1]);
+        assert_eq!(meta_order.to_amount, U128(9_405));
+        assert_eq!(contract.get_intent(meta_order.intent_id).unwrap().user_id.as_str(), "user.testnet");
+        assert!(contract.get_quote_request(request_id).unwrap().is_executed);
+    }
+
+    #[test]
+    fn settled_order_has_fusion_hash() {
+        let (mut contract, solvers, _) = setup_auction(&[100]);
+        set_context(&"user.testnet".parse().unwrap(), 0);
+        let request_id = contract.request_quote(
+            "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48.factory.bridge.near".parse().unwrap(),
+            "wrap.near".parse().unwrap(),
+            U128(1_000),
+            U64(1_000),
+            Some(U64(100)),
+            None,
+            None,
+            Some(hashlock()),
+        );
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 10);
+        set_context(&owner(), 100);
+        let order_id = contract.settle_quote(request_id);
+
+        let maker = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string();
+        let fusion_order = contract.get_fusion_order(order_id.clone(), maker.clone(), "0x0".to_string());
+        assert_eq!(fusion_order.maker_asset, "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        assert_eq!(fusion_order.taker_asset, fusion::BRIDGED_WNEAR.to_lowercase());
+        assert_eq!(fusion_order.taking_amount, U128(9_900));
+
+        let router = fusion::parse_address(fusion::AGGREGATION_ROUTER_V6).unwrap();
+        let hash = contract.get_fusion_order_hash(order_id, maker, "0x0".to_string(), 1);
+        assert_eq!(hash, fusion::to_hex(&fusion_order.hash(1, &router)));
+    }
+
+    #[test]
+    fn bid_with_taken_intent_id_is_skipped() {
+        let (mut contract, solvers, first_request) = setup_auction(&[100, 100]);
+        set_context(&"user.testnet".parse().unwrap(), 0);
+        let second_request = contract.request_quote(
+            "usdc.testnet".parse().unwrap(),
+            "wrap.testnet".parse().unwrap(),
+            U128(1_000),
+            U64(1_000),
+            Some(U64(100)),
+            None,
+            None,
+            Some(hashlock()),
+        );
+
+        // solver_0 bids on both requests with the same intent id
+        bid(&mut contract, &solvers[0], &first_request, 10_000, 10);
+        bid(&mut contract, &solvers[0], &second_request, 20_000, 10);
+        bid(&mut contract, &solvers[1], &second_request, 10_000, 20);
+
+        set_context(&owner(), 100);
+        contract.settle_quote(first_request);
+        let order_id = contract.settle_quote(second_request);
+        let meta_order = contract.get_meta_order(order_id).unwrap();
+        assert_eq!(meta_order.solver_id, solvers[1]);
+        assert_eq!(meta_order.intent_id, format!("intent_{}", solvers[1]));
+    }
+
+    #[test]
+    fn bid_from_solver_with_removed_measurement_is_skipped() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100, 100]);
+        bid(&mut contract, &solvers[0], &request_id, 20_000, 10);
+        bid(&mut contract, &solvers[1], &request_id, 10_000, 20);
+
+        // solver_0's enclave measurement is no longer on the allowlist
+        let mut tee_config = contract.get_tee_config(solvers[0].clone()).unwrap();
+        tee_config.tee_enclave_id = "22".repeat(48);
+        contract.tee_configs.insert(&solvers[0], &tee_config);
+
+        set_context(&owner(), 100);
+        let order_id = contract.settle_quote(request_id);
+        assert_eq!(contract.get_meta_order(order_id).unwrap().solver_id, solvers[1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "No valid bids")]
+    fn settle_rejects_bids_once_measurement_is_removed() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 10);
+        set_context(&owner(), 100);
+        contract.remove_tee_measurement(SGX_MRENCLAVE.to_string());
+        contract.settle_quote(request_id);
+    }
+
+    #[test]
+    fn tie_breaks_on_reputation() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100, 100]);
+        contract.solver_reputation.insert(&solvers[1], &1500);
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 10);
+        bid(&mut contract, &solvers[1], &request_id, 10_000, 20);
+
+        set_context(&owner(), 100);
+        let order_id = contract.settle_quote(request_id);
+        assert_eq!(contract.get_meta_order(order_id).unwrap().solver_id, solvers[1]);
+    }
+
+    #[test]
+    fn rebid_replaces_previous_bid() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 10);
+        bid(&mut contract, &solvers[0], &request_id, 20_000, 20);
+
+        let bids = contract.get_quote_bids(request_id);
+        assert_eq!(bids.len(), 1);
+        assert_eq!(bids[0].to_amount, U128(19_800));
+    }
+
+    #[test]
+    #[should_panic(expected = "Bidding closed")]
+    fn bids_rejected_after_window() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bidding still open")]
+    fn settle_waits_for_window() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 10);
+        set_context(&owner(), 50);
+        contract.settle_quote(request_id);
+    }
+
+    #[test]
+    fn dutch_auction_curve_decays_to_floor() {
+        let curve = DutchAuctionCurve {
+            start_amount: U128(10_000),
+            min_amount: U128(9_000),
+            step_time: U64(10),
+            step_amount: U128(300),
+        };
+        assert_eq!(curve.price_at(0), 10_000);
+        assert_eq!(curve.price_at(9), 10_000);
+        assert_eq!(curve.price_at(10), 9_700);
+        assert_eq!(curve.price_at(35), 9_100);
+        assert_eq!(curve.price_at(40), 9_000);
+        assert_eq!(curve.price_at(u64::MAX), 9_000);
+    }
+
+    fn request_with_curve(contract: &mut ShadeAgentSolver) -> String {
+        set_context(&"user.testnet".parse().unwrap(), 0);
+        let curve = DutchAuctionCurve {
+            start_amount: U128(10_000),
+            min_amount: U128(9_000),
+            step_time: U64(10),
+            step_amount: U128(500),
+        };
+        contract.request_quote(
+            "usdc.testnet".parse().unwrap(),
+            "wrap.testnet".parse().unwrap(),
+            U128(1_000),
+            U64(1_000),
+            Some(U64(100)),
+            Some(curve),
+            None,
+            Some(hashlock()),
+        )
+    }
+
+    #[test]
+    fn bid_accepted_at_curve_price() {
+        let (mut contract, solvers, _) = setup_auction(&[0]);
+        let request_id = request_with_curve(&mut contract);
+
+        set_context(&solvers[0], 10);
+        assert_eq!(contract.get_current_rate(request_id.clone()), Some(U128(9_500)));
+        assert_eq!(bid(&mut contract, &solvers[0], &request_id, 9_500, 10), U128(9_500));
+    }
+
+    #[test]
+    #[should_panic(expected = "Quote below auction price")]
+    fn bid_rejected_below_curve_price() {
+        let (mut contract, solvers, _) = setup_auction(&[0]);
+        let request_id = request_with_curve(&mut contract);
+        bid(&mut contract, &solvers[0], &request_id, 9_999, 5);
+    }
+
+    /// The single function call of a receipt: (method, JSON args, attached deposit)
+    fn function_call(receipt: &near_sdk::mock::Receipt) -> (String, serde_json::Value, u128) {
+        match &receipt.actions[..] {
+            [near_sdk::mock::MockAction::FunctionCallWeight { method_name, args, attached_deposit, .. }] => {
+                (String::from_utf8(method_name.clone()).unwrap(), serde_json::from_slice(args).unwrap(), attached_deposit.as_yoctonear())
+            }
+            actions => panic!("Not a single function call: {:?}", actions),
+        }
+    }
+
+    /// Settles a 1_000 -> 9_900 order won by `solver_0.testnet` and executes it, leaving
+    /// its HTLC lock in flight. Returns the receipts `execute_meta_order` created.
+    fn setup_executing_order() -> (ShadeAgentSolver, Vec<AccountId>, String, Vec<near_sdk::mock::Receipt>) {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 10);
+        set_context(&owner(), 100);
+        let order_id = contract.settle_quote(request_id);
+
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(solvers[0].clone()).block_timestamp(200).attached_deposit(NearToken::from_yoctonear(9_900));
+        testing_env!(context.build());
+        contract.execute_meta_order(order_id.clone());
+        (contract, solvers, order_id, near_sdk::test_utils::get_created_receipts())
+    }
+
+    #[test]
+    fn execute_locks_order_amount_in_htlc() {
+        let (contract, solvers, order_id, receipts) = setup_executing_order();
+        let intent_id = contract.get_meta_order(order_id.clone()).unwrap().intent_id;
+        assert_eq!(contract.get_intent(intent_id).unwrap().status, "executing");
+        assert_eq!(contract.get_meta_orders(Some("executing".to_string()), None, None)[0].order_id, order_id);
+        assert_eq!(receipts.len(), 2);
+
+        assert_eq!(receipts[0].receiver_id.as_str(), "htlc.testnet");
+        let (method, args, deposit) = function_call(&receipts[0]);
+        assert_eq!(method, "lock_near");
+        assert_eq!(deposit, 9_900);
+        assert_eq!(
+            args,
+            serde_json::json!({
+                "lock": {
+                    "hashlock": hashlock(),
+                    "timelock": HTLC_TIMELOCK,
+                    "exclusive_period": null,
+                    "dest_chain": "near",
+                    "dest_user": "user.testnet",
+                    "min_return": "9900",
+                    "output_token": "wrap.testnet",
+                    "resolver_id": "user.testnet",
+                },
+                "refund_to": solvers[0],
+            })
+        );
+
+        assert_eq!(receipts[1].receiver_id, env::current_account_id());
+        let (method, args, deposit) = function_call(&receipts[1]);
+        assert_eq!(method, "on_meta_order_executed");
+        assert_eq!(deposit, 0);
+        assert_eq!(args, serde_json::json!({ "order_id": order_id, "solver_id": solvers[0] }));
+    }
+
+    /// Runs the `on_meta_order_executed` callback scheduled in `receipts` with `result`
+    fn resolve_execution(contract: &mut ShadeAgentSolver, receipts: &[near_sdk::mock::Receipt], result: PromiseResult) -> bool {
+        let (_, args, _) = function_call(&receipts[1]);
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(owner()).current_account_id(owner());
+        testing_env!(
+            context.build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result]
+        );
+        contract.on_meta_order_executed(
+            serde_json::from_value(args["order_id"].clone()).unwrap(),
+            serde_json::from_value(args["solver_id"].clone()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn successful_lock_executes_order() {
+        let (mut contract, solvers, order_id, receipts) = setup_executing_order();
+        let reputation = contract.get_solver_reputation(solvers[0].clone());
+        assert!(resolve_execution(&mut contract, &receipts, PromiseResult::Successful(vec![])));
+
+        let meta_order = contract.get_meta_order(order_id.clone()).unwrap();
+        assert!(meta_order.is_executed);
+        assert_eq!(meta_order.filled_amount, U128(1_000));
+        assert_eq!(meta_order.remaining_amount, U128(0));
+        assert_eq!(contract.get_intent(meta_order.intent_id).unwrap().status, "executed");
+        assert_eq!(contract.get_solver_reputation(solvers[0].clone()), reputation + 10);
+        assert!(near_sdk::test_utils::get_created_receipts().is_empty());
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![Event::MetaOrderExecuted { order_id, solver_id: solvers[0].clone(), success: true, hashlock: None }.to_log()]
+        );
+    }
+
+    #[test]
+    fn failed_lock_refunds_solver() {
+        let (mut contract, solvers, order_id, receipts) = setup_executing_order();
+        let reputation = contract.get_solver_reputation(solvers[0].clone());
+        assert!(!resolve_execution(&mut contract, &receipts, PromiseResult::Failed));
+
+        let meta_order = contract.get_meta_order(order_id).unwrap();
+        assert!(!meta_order.is_executed);
+        assert_eq!(meta_order.filled_amount, U128(0));
+        assert_eq!(contract.get_intent(meta_order.intent_id).unwrap().status, "failed");
+        assert_eq!(contract.get_solver_reputation(solvers[0].clone()), reputation - 10);
+        assert!(contract.get_meta_orders(Some("open".to_string()), None, None).is_empty());
+        assert_eq!(contract.get_meta_orders(Some("failed".to_string()), None, None).len(), 1);
+
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, solvers[0]);
+        assert!(matches!(
+            receipts[0].actions[..],
+            [near_sdk::mock::MockAction::Transfer { deposit, .. }] if deposit == NearToken::from_yoctonear(9_900)
+        ));
+    }
+
+    #[test]
+    fn executed_order_removal_credits_requester() {
+        let (mut contract, _, order_id, receipts) = setup_executing_order();
+        let user: AccountId = "user.testnet".parse().unwrap();
+        assert!(resolve_execution(&mut contract, &receipts, PromiseResult::Successful(vec![])));
+
+        set_context(&owner(), 300);
+        contract.remove_meta_order(order_id.clone());
+        assert!(contract.get_meta_order(order_id.clone()).is_none());
+        assert!(contract.get_intents_by_user(user.clone(), None, None).is_empty());
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![Event::MetaOrderRemoved { order_id }.to_log()]);
+
+        set_context(&owner(), 1_001);
+        contract.remove_expired_quote("quote_0".to_string());
+        let balance = contract.storage_balance_of(user).unwrap();
+        assert_eq!(balance.available.0, balance.total.0 - StorageLedger::bounds().min.0);
+    }
+
+    #[test]
+    fn expired_order_can_be_removed() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 10);
+        set_context(&owner(), 100);
+        let order_id = contract.settle_quote(request_id);
+        let intent_id = contract.get_meta_order(order_id.clone()).unwrap().intent_id;
+
+        set_context(&owner(), 1_001);
+        contract.remove_meta_order(order_id.clone());
+        assert!(contract.get_meta_order(order_id).is_none());
+        assert!(contract.get_intent(intent_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Order not finished")]
+    fn open_order_cannot_be_removed() {
+        let (mut contract, solvers, request_id) = setup_auction(&[100]);
+        bid(&mut contract, &solvers[0], &request_id, 10_000, 10);
+        set_context(&owner(), 100);
+        let order_id = contract.settle_quote(request_id);
+        contract.remove_meta_order(order_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Order is executing")]
+    fn executing_order_cannot_be_removed() {
+        let (mut contract, _, order_id, _) = setup_executing_order();
+        set_context(&owner(), 1_001);
+        contract.remove_meta_order(order_id);
+    }
+
+    /// Secret `i` is 32 bytes of `i`, one per part plus one for completion
+    fn fill_secrets(parts: u32) -> (Vec<Vec<u8>>, Vec<[u8; 32]>) {
+        let secrets: Vec<Vec<u8>> = (0..=parts).map(|i| vec![i as u8; 32]).collect();
+        let leaves = secrets.iter().enumerate().map(|(i, secret)| merkle::leaf(i as u64, &env::sha256_array(secret))).collect();
+        (secrets, leaves)
+    }
+
+    /// Settles a 4-part order of 1_000 -> 10_000 won by `solver_0.testnet`
+    fn setup_partial_order() -> (ShadeAgentSolver, Vec<AccountId>, String, Vec<[u8; 32]>) {
+        setup_partial_order_for(10_000)
+    }
+
+    /// Settles a 4-part order of 1_000 -> `to_amount` won by `solver_0.testnet`
+    fn setup_partial_order_for(to_amount: u128) -> (ShadeAgentSolver, Vec<AccountId>, String, Vec<[u8; 32]>) {
+        let (mut contract, solvers, _) = setup_auction(&[0]);
+        let (_, leaves) = fill_secrets(4);
+
+        set_context(&"user.testnet".parse().unwrap(), 0);
+        let partial_fill = PartialFillConfig {
+            secrets_merkle_root: hex::encode(merkle::root(&leaves)),
+            parts: 4,
+        };
+        let request_id = contract.request_quote(
+            "usdc.testnet".parse().unwrap(),
+            "wrap.testnet".parse().unwrap(),
+            U128(1_000),
+            U64(1_000),
+            Some(U64(100)),
+            None,
+            Some(partial_fill),
+            None,
+        );
+        bid(&mut contract, &solvers[0], &request_id, to_amount, 10);
+        set_context(&owner(), 100);
+        let order_id = contract.settle_quote(request_id);
+        (contract, solvers, order_id, leaves)
+    }
+
+    #[test]
+    #[should_panic(expected = "Partially fillable orders are locked under their merkle secrets")]
+    fn partial_request_rejects_single_hashlock() {
+        let (mut contract, _, _) = setup_auction(&[0]);
+        let (_, leaves) = fill_secrets(4);
+        set_context(&"user.testnet".parse().unwrap(), 0);
+        let partial_fill = PartialFillConfig {
+            secrets_merkle_root: hex::encode(merkle::root(&leaves)),
+            parts: 4,
+        };
+        contract.request_quote("usdc.testnet".parse().unwrap(), "wrap.testnet".parse().unwrap(), U128(1_000), U64(1_000), Some(U64(100)), None, Some(partial_fill), Some(hashlock()));
+    }
+
+    /// Fills `parts` quarters of the order, attaching 2_500 per part. Returns the receipts
+    /// `fill_meta_order` created.
+    fn fill(contract: &mut ShadeAgentSolver, solver_id: &AccountId, order_id: &str, parts: u32, secret_index: u32, leaves: &[[u8; 32]]) -> Vec<near_sdk::mock::Receipt> {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(solver_id.clone()).block_timestamp(200).attached_deposit(NearToken::from_yoctonear(2_500 * parts as u128));
+        testing_env!(context.build());
+        let proof = merkle::proof(leaves, secret_index as usize).iter().map(hex::encode).collect();
+        let secret_hash = env::sha256_array(&[secret_index as u8; 32]);
+        contract.fill_meta_order(order_id.to_string(), parts, secret_index, hex::encode(secret_hash), proof);
+        near_sdk::test_utils::get_created_receipts()
+    }
+
+    #[test]
+    #[should_panic(expected = "Fill amount too small")]
+    fn fill_rejects_zero_taking_amount() {
+        let (mut contract, solvers, order_id, leaves) = setup_partial_order_for(3);
+        set_context(&solvers[0], 200);
+        let proof = merkle::proof(&leaves, 0).iter().map(hex::encode).collect();
+        contract.fill_meta_order(order_id, 1, 0, hex::encode(env::sha256_array(&[0u8; 32])), proof);
+    }
+
+    #[test]
+    fn small_order_fills_in_larger_chunks() {
+        let (mut contract, solvers, order_id, leaves) = setup_partial_order_for(3);
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(solvers[0].clone()).block_timestamp(200).attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let proof = merkle::proof(&leaves, 1).iter().map(hex::encode).collect();
+        contract.fill_meta_order(order_id, 2, 1, hex::encode(env::sha256_array(&[1u8; 32])), proof);
+        assert_eq!(near_sdk::test_utils::get_created_receipts().len(), 2);
+    }
+
+    /// Runs the `on_meta_order_filled` callback scheduled in `receipts` with `result`
+    fn resolve_fill(contract: &mut ShadeAgentSolver, receipts: &[near_sdk::mock::Receipt], result: PromiseResult) -> bool {
+        let (_, args, _) = function_call(&receipts[1]);
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(owner()).current_account_id(owner());
+        testing_env!(
+            context.build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result]
+        );
+        contract.on_meta_order_filled(
+            serde_json::from_value(args["order_id"].clone()).unwrap(),
+            serde_json::from_value(args["fill"].clone()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn fill_locks_proportional_share_in_htlc() {
+        let (mut contract, solvers, order_id, leaves) = setup_partial_order();
+        let receipts = fill(&mut contract, &solvers[0], &order_id, 1, 0, &leaves);
+        let secret_hash = hex::encode(env::sha256_array(&[0u8; 32]));
+        assert_eq!(receipts.len(), 2);
+
+        assert_eq!(receipts[0].receiver_id.as_str(), "htlc.testnet");
+        let (method, args, deposit) = function_call(&receipts[0]);
+        assert_eq!(method, "lock_near");
+        assert_eq!(deposit, 2_500);
+        assert_eq!(args["lock"]["hashlock"], secret_hash);
+        assert_eq!(args["lock"]["min_return"], "2500");
+        assert_eq!(args["lock"]["resolver_id"], "user.testnet");
+        assert_eq!(args["refund_to"], solvers[0].as_str());
+
+        assert_eq!(receipts[1].receiver_id, env::current_account_id());
+        let (method, args, deposit) = function_call(&receipts[1]);
+        assert_eq!(method, "on_meta_order_filled");
+        assert_eq!(deposit, 0);
+        assert_eq!(args["order_id"], order_id);
+        assert_eq!(args["fill"]["solver_id"], solvers[0].as_str());
+        assert_eq!(args["fill"]["making_amount"], "250");
+        assert_eq!(args["fill"]["taking_amount"], "2500");
+        assert_eq!(args["fill"]["filled_amount"], "250");
+        assert_eq!(args["fill"]["secret_index"], 0);
+        assert_eq!(args["fill"]["hashlock"], secret_hash);
+    }
+
+    #[test]
+    fn partial_fills_complete_order() {
+        let (mut contract, solvers, order_id, leaves) = setup_partial_order();
+
+        let receipts = fill(&mut contract, &solvers[0], &order_id, 1, 0, &leaves);
+        assert!(resolve_fill(&mut contract, &receipts, PromiseResult::Successful(vec![])));
+        let meta_order = contract.get_meta_order(order_id.clone()).unwrap();
+        assert_eq!(meta_order.filled_amount, U128(250));
+        assert_eq!(meta_order.remaining_amount, U128(750));
+        assert!(!meta_order.is_executed);
+        assert_eq!(contract.get_intent(meta_order.intent_id.clone()).unwrap().status, "pending");
+
+        // The fill completing the order reveals the extra secret
+        let receipts = fill(&mut contract, &solvers[0], &order_id, 3, 4, &leaves);
+        assert_eq!(function_call(&receipts[0]).2, 7_500);
+        assert!(resolve_fill(&mut contract, &receipts, PromiseResult::Successful(vec![])));
+        let meta_order = contract.get_meta_order(order_id.clone()).unwrap();
+        assert_eq!(meta_order.remaining_amount, U128(0));
+        assert!(meta_order.is_executed);
+        assert_eq!(contract.get_intent(meta_order.intent_id).unwrap().status, "executed");
+
+        let fills = contract.get_order_fills(order_id);
+        assert_eq!(fills.len(), 2);
+        assert_eq!(fills[1].making_amount, U128(750));
+        assert_eq!(fills[1].taking_amount, U128(7_500));
+    }
+
+    #[test]
+    fn failed_fill_leaves_order_open() {
+        let (mut contract, solvers, order_id, leaves) = setup_partial_order();
+        let receipts = fill(&mut contract, &solvers[0], &order_id, 1, 0, &leaves);
+        assert!(!resolve_fill(&mut contract, &receipts, PromiseResult::Failed));
+
+        let meta_order = contract.get_meta_order(order_id.clone()).unwrap();
+        assert_eq!(meta_order.filled_amount, U128(0));
+        assert_eq!(contract.get_intent(meta_order.intent_id).unwrap().status, "pending");
+        assert!(contract.get_order_fills(order_id).is_empty());
+
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, solvers[0]);
+        assert!(matches!(
+            receipts[0].actions[..],
+            [near_sdk::mock::MockAction::Transfer { deposit, .. }] if deposit == NearToken::from_yoctonear(2_500)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong secret index")]
+    fn fill_rejects_wrong_secret_index() {
+        let (mut contract, solvers, order_id, leaves) = setup_partial_order();
+        // One part out of four must reveal secret 0
+        fill(&mut contract, &solvers[0], &order_id, 1, 1, &leaves);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid merkle proof")]
+    fn fill_rejects_secret_outside_tree() {
+        let (mut contract, solvers, order_id, _) = setup_partial_order();
+        let (_, other_leaves) = fill_secrets(5);
+        fill(&mut contract, &solvers[0], &order_id, 1, 0, &other_leaves[1..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid merkle proof")]
+    fn fill_takes_the_secret_hash_not_the_secret() {
+        let (mut contract, solvers, order_id, leaves) = setup_partial_order();
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(solvers[0].clone()).block_timestamp(200).attached_deposit(NearToken::from_yoctonear(2_500));
+        testing_env!(context.build());
+        let proof = merkle::proof(&leaves, 0).iter().map(hex::encode).collect();
+        contract.fill_meta_order(order_id, 1, 0, hex::encode([0u8; 32]), proof);
+    }
+
+    #[test]
+    #[should_panic(expected = "Order is partially fillable, use fill_meta_order")]
+    fn partial_order_cannot_be_executed_at_once() {
+        let (mut contract, solvers, order_id, _) = setup_partial_order();
+        set_context(&solvers[0], 200);
+        contract.execute_meta_order(order_id);
+    }
+}
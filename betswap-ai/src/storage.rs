@@ -0,0 +1,280 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, AccountId, IntoStorageKey, NearToken, Promise};
+
+use crate::events::Event;
+
+/// Bytes charged on registration for the account's own ledger entry
+pub const ACCOUNT_STORAGE_BYTES: u64 = 128;
+
+/// NEP-145 storage balance of an account
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+/// NEP-145 bounds of a storage balance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct StorageAccount {
+    deposit: u128,
+    used_bytes: u64,
+}
+
+/// NEP-145 storage deposits, charged for the bytes of the records each account creates
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct StorageLedger {
+    accounts: LookupMap<AccountId, StorageAccount>,
+}
+
+impl StorageLedger {
+    pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
+        Self {
+            accounts: LookupMap::new(prefix),
+        }
+    }
+
+    pub fn bounds() -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(Self::cost(ACCOUNT_STORAGE_BYTES)),
+            max: None,
+        }
+    }
+
+    pub fn balance_of(&self, account_id: &AccountId) -> Option<StorageBalance> {
+        self.accounts.get(account_id).map(|account| StorageBalance {
+            total: U128(account.deposit),
+            available: U128(account.deposit - Self::cost(account.used_bytes)),
+        })
+    }
+
+    /// Add the attached deposit to `account_id`, registering it if needed. With
+    /// `registration_only`, anything above the minimum balance is refunded.
+    pub fn deposit(&mut self, account_id: AccountId, registration_only: bool) -> StorageBalance {
+        let amount = env::attached_deposit().as_yoctonear();
+        let min = Self::cost(ACCOUNT_STORAGE_BYTES);
+        let (account, refund) = match self.accounts.get(&account_id) {
+            Some(account) if registration_only => (account, amount),
+            Some(mut account) => {
+                account.deposit += amount;
+                (account, 0)
+            }
+            None => {
+                assert!(amount >= min, "Deposit below the minimum storage balance");
+                let deposit = if registration_only { min } else { amount };
+                let account = StorageAccount {
+                    deposit,
+                    used_bytes: ACCOUNT_STORAGE_BYTES,
+                };
+                (account, amount - deposit)
+            }
+        };
+        self.accounts.insert(&account_id, &account);
+
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(refund));
+        }
+        Event::StorageDeposited {
+            account_id: account_id.clone(),
+            amount: U128(amount - refund),
+        }
+        .emit();
+        self.balance_of(&account_id).unwrap()
+    }
+
+    /// Send up to the available balance of the caller back to them, all of it when `amount` is `None`
+    pub fn withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        near_sdk::assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut account = self.accounts.get(&account_id).expect("Account not registered");
+        let available = account.deposit - Self::cost(account.used_bytes);
+        let amount = amount.map_or(available, |amount| amount.0);
+        assert!(amount <= available, "Amount exceeds available storage balance");
+
+        account.deposit -= amount;
+        self.accounts.insert(&account_id, &account);
+
+        if amount > 0 {
+            Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(amount));
+        }
+        Event::StorageWithdrawn {
+            account_id: account_id.clone(),
+            amount: U128(amount),
+        }
+        .emit();
+        self.balance_of(&account_id).unwrap()
+    }
+
+    /// Close the caller's account and send back its whole deposit. Returns `false` if it
+    /// wasn't registered. Records can't be dropped on the owner's behalf, so the account
+    /// must not own any, even with `force`.
+    pub fn unregister(&mut self, force: bool) -> bool {
+        near_sdk::assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let account = match self.accounts.get(&account_id) {
+            Some(account) => account,
+            None => return false,
+        };
+        let message = if force { "Records can't be force-removed" } else { "Account still owns records" };
+        assert!(account.used_bytes == ACCOUNT_STORAGE_BYTES, "{}", message);
+
+        self.accounts.remove(&account_id);
+        Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(account.deposit));
+        Event::StorageUnregistered {
+            account_id,
+            amount: U128(account.deposit),
+        }
+        .emit();
+        true
+    }
+
+    /// Charge `account_id` for the bytes used since `initial_usage`, or credit back the bytes freed
+    pub fn settle(&mut self, account_id: &AccountId, initial_usage: u64) {
+        let usage = env::storage_usage();
+        let mut account = self.accounts.get(account_id).expect("Account not registered for storage");
+        if usage >= initial_usage {
+            account.used_bytes += usage - initial_usage;
+            assert!(Self::cost(account.used_bytes) <= account.deposit, "Insufficient storage deposit");
+        } else {
+            account.used_bytes = account.used_bytes.saturating_sub(initial_usage - usage).max(ACCOUNT_STORAGE_BYTES);
+        }
+        self.accounts.insert(account_id, &account);
+    }
+
+    fn cost(bytes: u64) -> u128 {
+        bytes as u128 * env::storage_byte_cost().as_yoctonear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    fn alice() -> AccountId {
+        "alice.testnet".parse().unwrap()
+    }
+
+    fn set_context(deposit: u128) {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(alice()).attached_deposit(NearToken::from_yoctonear(deposit));
+        testing_env!(context.build());
+    }
+
+    fn min() -> u128 {
+        StorageLedger::bounds().min.0
+    }
+
+    #[test]
+    fn registration_charges_the_account_entry() {
+        set_context(min() * 3);
+        let mut ledger = StorageLedger::new(b"s".as_slice());
+        let balance = ledger.deposit(alice(), false);
+        assert_eq!(balance.total, U128(min() * 3));
+        assert_eq!(balance.available, U128(min() * 2));
+    }
+
+    #[test]
+    fn registration_only_keeps_the_minimum() {
+        set_context(min() * 3);
+        let mut ledger = StorageLedger::new(b"s".as_slice());
+        let balance = ledger.deposit(alice(), true);
+        assert_eq!(balance.total, U128(min()));
+        assert_eq!(balance.available, U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit below the minimum storage balance")]
+    fn registration_requires_minimum() {
+        set_context(min() - 1);
+        StorageLedger::new(b"s".as_slice()).deposit(alice(), false);
+    }
+
+    #[test]
+    fn settle_charges_and_credits_bytes() {
+        set_context(min() * 3);
+        let mut ledger = StorageLedger::new(b"s".as_slice());
+        ledger.deposit(alice(), false);
+
+        let initial_usage = env::storage_usage();
+        env::storage_write(b"record", &[0u8; 64]);
+        ledger.settle(&alice(), initial_usage);
+        let charged = ledger.balance_of(&alice()).unwrap().available;
+        assert!(charged.0 < min() * 2);
+
+        let initial_usage = env::storage_usage();
+        env::storage_remove(b"record");
+        ledger.settle(&alice(), initial_usage);
+        assert_eq!(ledger.balance_of(&alice()).unwrap().available, U128(min() * 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient storage deposit")]
+    fn settle_requires_deposit() {
+        set_context(min());
+        let mut ledger = StorageLedger::new(b"s".as_slice());
+        ledger.deposit(alice(), false);
+
+        let initial_usage = env::storage_usage();
+        env::storage_write(b"record", &[0u8; 64]);
+        ledger.settle(&alice(), initial_usage);
+    }
+
+    #[test]
+    fn withdraw_available_balance() {
+        set_context(min() * 3);
+        let mut ledger = StorageLedger::new(b"s".as_slice());
+        ledger.deposit(alice(), false);
+
+        set_context(1);
+        let balance = ledger.withdraw(None);
+        assert_eq!(balance.total, U128(min()));
+        assert_eq!(balance.available, U128(0));
+    }
+
+    #[test]
+    fn unregister_returns_the_deposit() {
+        set_context(min() * 3);
+        let mut ledger = StorageLedger::new(b"s".as_slice());
+        ledger.deposit(alice(), false);
+
+        set_context(1);
+        assert!(ledger.unregister(false));
+        assert!(ledger.balance_of(&alice()).is_none());
+        assert!(!ledger.unregister(false));
+    }
+
+    #[test]
+    #[should_panic(expected = "Account still owns records")]
+    fn unregister_requires_no_records() {
+        set_context(min() * 3);
+        let mut ledger = StorageLedger::new(b"s".as_slice());
+        ledger.deposit(alice(), false);
+
+        let initial_usage = env::storage_usage();
+        env::storage_write(b"record", &[0u8; 64]);
+        ledger.settle(&alice(), initial_usage);
+        set_context(1);
+        ledger.unregister(false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount exceeds available storage balance")]
+    fn withdraw_cannot_touch_used_storage() {
+        set_context(min() * 3);
+        let mut ledger = StorageLedger::new(b"s".as_slice());
+        ledger.deposit(alice(), false);
+
+        set_context(1);
+        ledger.withdraw(Some(U128(min() * 2 + 1)));
+    }
+}
@@ -0,0 +1,255 @@
+use near_sdk::env;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+
+/// Size of the quote header shared by SGX and TDX DCAP quotes
+const HEADER_LEN: usize = 48;
+
+/// Size of the SGX enclave report body
+const SGX_REPORT_BODY_LEN: usize = 384;
+
+/// Size of the TDX 1.0 TD report body
+const TDX_REPORT_BODY_LEN: usize = 584;
+
+/// Sizes of the ECDSA-P256 quote signature (r || s) and attestation key (x || y)
+/// at the start of the signature data
+const SIGNATURE_LEN: usize = 64;
+pub const ATTESTATION_KEY_LEN: usize = 64;
+
+/// `tee_type` values in the quote header
+const TEE_TYPE_SGX: u32 = 0x0000_0000;
+const TEE_TYPE_TDX: u32 = 0x0000_0081;
+
+/// Offsets into the report body
+const SGX_MRENCLAVE_OFFSET: usize = 64;
+const SGX_REPORT_DATA_OFFSET: usize = 320;
+const TDX_MRTD_OFFSET: usize = 136;
+const TDX_REPORT_DATA_OFFSET: usize = 520;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TeeType {
+    Sgx,
+    Tdx,
+}
+
+/// Fields of a DCAP quote the contract checks
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quote {
+    pub version: u16,
+    pub tee_type: TeeType,
+    /// MRENCLAVE (32 bytes) for SGX, MRTD (48 bytes) for TDX
+    pub measurement: Vec<u8>,
+    pub report_data: [u8; 64],
+    /// Key of the quoting enclave that signed the header and report body
+    pub attestation_key: [u8; ATTESTATION_KEY_LEN],
+}
+
+impl Quote {
+    /// The enclave binds its key by putting `sha256(public_key)` in the first
+    /// half of the report data
+    pub fn binds_public_key(&self, public_key: &str) -> bool {
+        self.report_data[..32] == env::sha256_array(public_key.as_bytes())
+    }
+}
+
+/// Parse an SGX (v3) or TDX (v4) DCAP quote and check its ECDSA-P256 signature
+/// over the header and report body against the attestation key it carries.
+///
+/// This only proves the quote was signed by that key. The PCK certificate chain
+/// and QE report that certify the key are not checked here, so callers must only
+/// trust attestation keys they have pinned after checking them off-chain.
+pub fn verify_quote(quote: &[u8]) -> Option<Quote> {
+    if quote.len() < HEADER_LEN {
+        return None;
+    }
+    let version = u16::from_le_bytes([quote[0], quote[1]]);
+    let tee_type = u32::from_le_bytes([quote[4], quote[5], quote[6], quote[7]]);
+    let body = &quote[HEADER_LEN..];
+
+    let (tee_type, body_len, measurement, report_data) = match (version, tee_type) {
+        (3, TEE_TYPE_SGX) if body.len() >= SGX_REPORT_BODY_LEN => (
+            TeeType::Sgx,
+            SGX_REPORT_BODY_LEN,
+            &body[SGX_MRENCLAVE_OFFSET..SGX_MRENCLAVE_OFFSET + 32],
+            &body[SGX_REPORT_DATA_OFFSET..SGX_REPORT_DATA_OFFSET + 64],
+        ),
+        (4, TEE_TYPE_TDX) if body.len() >= TDX_REPORT_BODY_LEN => (
+            TeeType::Tdx,
+            TDX_REPORT_BODY_LEN,
+            &body[TDX_MRTD_OFFSET..TDX_MRTD_OFFSET + 48],
+            &body[TDX_REPORT_DATA_OFFSET..TDX_REPORT_DATA_OFFSET + 64],
+        ),
+        _ => return None,
+    };
+
+    // The signature data follows the body, prefixed with its little-endian length
+    let signed = &quote[..HEADER_LEN + body_len];
+    let rest = &quote[HEADER_LEN + body_len..];
+    let signature_data_len = u32::from_le_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+    let signature_data = rest.get(4..4 + signature_data_len)?;
+    let signature = signature_data.get(..SIGNATURE_LEN)?;
+    let attestation_key: [u8; ATTESTATION_KEY_LEN] = signature_data.get(SIGNATURE_LEN..SIGNATURE_LEN + ATTESTATION_KEY_LEN)?.try_into().ok()?;
+    if !verify_signature(signed, signature, &attestation_key) {
+        return None;
+    }
+
+    Some(Quote {
+        version,
+        tee_type,
+        measurement: measurement.to_vec(),
+        report_data: report_data.try_into().ok()?,
+        attestation_key,
+    })
+}
+
+/// Whether `key` is a valid uncompressed P-256 point (x || y)
+pub fn is_valid_attestation_key(key: &[u8]) -> bool {
+    key.len() == ATTESTATION_KEY_LEN && verifying_key(key).is_some()
+}
+
+fn verifying_key(key: &[u8]) -> Option<VerifyingKey> {
+    VerifyingKey::from_sec1_bytes(&[&[0x04], key].concat()).ok()
+}
+
+/// ECDSA-P256 with SHA-256, `signature` being r || s
+fn verify_signature(message: &[u8], signature: &[u8], key: &[u8]) -> bool {
+    match (verifying_key(key), Signature::from_slice(signature)) {
+        (Some(key), Ok(signature)) => key.verify(message, &signature).is_ok(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use p256::ecdsa::signature::Signer;
+    use p256::ecdsa::SigningKey;
+
+    const PUBLIC_KEY: &str = "ed25519:8ocU7FbgmTDA4egSLPguXXPzm9MAfyPVP7m39Yc6ji6G";
+
+    fn signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32].into()).unwrap()
+    }
+
+    fn key_bytes(key: &SigningKey) -> Vec<u8> {
+        key.verifying_key().to_encoded_point(false).as_bytes()[1..].to_vec()
+    }
+
+    /// Hex attestation key (x || y) the sample quotes are signed with
+    pub(crate) fn attestation_key() -> String {
+        hex::encode(key_bytes(&signing_key(7)))
+    }
+
+    /// Append the signature data: its length, the signature over header || body by `key`, then `claimed_key`
+    fn sign_with(mut quote: Vec<u8>, key: &SigningKey, claimed_key: &[u8]) -> Vec<u8> {
+        let signature: Signature = key.sign(&quote);
+        quote.extend_from_slice(&((SIGNATURE_LEN + ATTESTATION_KEY_LEN) as u32).to_le_bytes());
+        quote.extend_from_slice(&signature.to_bytes());
+        quote.extend_from_slice(claimed_key);
+        quote
+    }
+
+    fn sign(quote: Vec<u8>) -> Vec<u8> {
+        let key = signing_key(7);
+        let claimed_key = key_bytes(&key);
+        sign_with(quote, &key, &claimed_key)
+    }
+
+    fn unsigned_sgx_quote(public_key: &str) -> Vec<u8> {
+        let mut quote = vec![0u8; HEADER_LEN + SGX_REPORT_BODY_LEN];
+        quote[0..2].copy_from_slice(&3u16.to_le_bytes());
+        quote[2..4].copy_from_slice(&2u16.to_le_bytes()); // ECDSA-256 attestation key
+        let body = HEADER_LEN;
+        quote[body + SGX_MRENCLAVE_OFFSET..body + SGX_MRENCLAVE_OFFSET + 32].fill(0x11);
+        quote[body + SGX_REPORT_DATA_OFFSET..body + SGX_REPORT_DATA_OFFSET + 32]
+            .copy_from_slice(&env::sha256_array(public_key.as_bytes()));
+        quote
+    }
+
+    /// SGX v3 quote with MRENCLAVE 0x11.. and the key hash in the report data,
+    /// signed by the test attestation key
+    pub(crate) fn sample_sgx_quote_for(public_key: &str) -> Vec<u8> {
+        sign(unsigned_sgx_quote(public_key))
+    }
+
+    fn sample_sgx_quote() -> Vec<u8> {
+        sample_sgx_quote_for(PUBLIC_KEY)
+    }
+
+    /// TDX v4 quote with MRTD 0x22.., signed by the test attestation key
+    fn sample_tdx_quote() -> Vec<u8> {
+        let mut quote = vec![0u8; HEADER_LEN + TDX_REPORT_BODY_LEN];
+        quote[0..2].copy_from_slice(&4u16.to_le_bytes());
+        quote[2..4].copy_from_slice(&2u16.to_le_bytes());
+        quote[4..8].copy_from_slice(&TEE_TYPE_TDX.to_le_bytes());
+        let body = HEADER_LEN;
+        quote[body + TDX_MRTD_OFFSET..body + TDX_MRTD_OFFSET + 48].fill(0x22);
+        quote[body + TDX_REPORT_DATA_OFFSET..body + TDX_REPORT_DATA_OFFSET + 32]
+            .copy_from_slice(&env::sha256_array(PUBLIC_KEY.as_bytes()));
+        sign(quote)
+    }
+
+    #[test]
+    fn verifies_sgx_quote() {
+        let quote = verify_quote(&sample_sgx_quote()).unwrap();
+        assert_eq!(quote.tee_type, TeeType::Sgx);
+        assert_eq!(quote.measurement, vec![0x11; 32]);
+        assert_eq!(hex::encode(quote.attestation_key), attestation_key());
+        assert!(quote.binds_public_key(PUBLIC_KEY));
+    }
+
+    #[test]
+    fn verifies_tdx_quote() {
+        let quote = verify_quote(&sample_tdx_quote()).unwrap();
+        assert_eq!(quote.tee_type, TeeType::Tdx);
+        assert_eq!(quote.measurement, vec![0x22; 48]);
+        assert!(quote.binds_public_key(PUBLIC_KEY));
+    }
+
+    #[test]
+    fn rejects_other_public_key() {
+        let quote = verify_quote(&sample_sgx_quote()).unwrap();
+        assert!(!quote.binds_public_key("ed25519:DcA2MzgpJbrUATQLLceocVckhhAqrkingax4oJ9kZ847"));
+    }
+
+    #[test]
+    fn rejects_tampered_body() {
+        let mut quote = sample_sgx_quote();
+        quote[HEADER_LEN + SGX_MRENCLAVE_OFFSET] = 0x12;
+        assert_eq!(verify_quote(&quote), None);
+    }
+
+    #[test]
+    fn rejects_signature_by_another_key() {
+        let quote = sign_with(unsigned_sgx_quote(PUBLIC_KEY), &signing_key(8), &hex::decode(attestation_key()).unwrap());
+        assert_eq!(verify_quote(&quote), None);
+    }
+
+    #[test]
+    fn rejects_missing_signature_data() {
+        assert_eq!(verify_quote(&unsigned_sgx_quote(PUBLIC_KEY)), None);
+        let quote = sample_sgx_quote();
+        assert_eq!(verify_quote(&quote[..quote.len() - 1]), None);
+    }
+
+    #[test]
+    fn validates_attestation_keys() {
+        assert!(is_valid_attestation_key(&hex::decode(attestation_key()).unwrap()));
+        assert!(!is_valid_attestation_key(&[1u8; ATTESTATION_KEY_LEN]));
+        assert!(!is_valid_attestation_key(&[1u8; 32]));
+    }
+
+    #[test]
+    fn rejects_truncated_or_unknown_quotes() {
+        assert_eq!(verify_quote(&[]), None);
+        assert_eq!(verify_quote(&sample_sgx_quote()[..HEADER_LEN + 100]), None);
+
+        let mut unknown_version = sample_sgx_quote();
+        unknown_version[0] = 5;
+        assert_eq!(verify_quote(&unknown_version), None);
+
+        let mut mismatched_type = sample_sgx_quote();
+        mismatched_type[4..8].copy_from_slice(&TEE_TYPE_TDX.to_le_bytes());
+        assert_eq!(verify_quote(&mismatched_type), None);
+    }
+}